@@ -17,39 +17,67 @@ export interface TextMetrics {
   alphabeticBaseline: number
   ideographicBaseline: number
 }
-export const enum CompositeOperation {
-  SourceOver = 'SourceOver',
-  SourceIn = 'SourceIn',
-  SourceOut = 'SourceOut',
-  SourceAtop = 'SourceAtop',
-  DestinationOver = 'DestinationOver',
-  DestinationIn = 'DestinationIn',
-  DestinationOut = 'DestinationOut',
-  DestinationAtop = 'DestinationAtop',
-  Lighter = 'Lighter',
-  Copy = 'Copy',
-  Xor = 'Xor',
-  Multiply = 'Multiply',
-  Screen = 'Screen',
-  Overlay = 'Overlay',
-  Darken = 'Darken',
-  Lighten = 'Lighten',
-  ColorDodge = 'ColorDodge',
-  ColorBurn = 'ColorBurn',
-  HardLight = 'HardLight',
-  SoftLight = 'SoftLight',
-  Difference = 'Difference',
-  Exclusion = 'Exclusion'
+export interface DOMMatrix2DInit {
+  a?: number
+  b?: number
+  c?: number
+  d?: number
+  e?: number
+  f?: number
+  m11?: number
+  m12?: number
+  m21?: number
+  m22?: number
+  m41?: number
+  m42?: number
+}
+export interface DOMMatrixInit {
+  a?: number
+  b?: number
+  c?: number
+  d?: number
+  e?: number
+  f?: number
+  m11?: number
+  m12?: number
+  m13?: number
+  m14?: number
+  m21?: number
+  m22?: number
+  m23?: number
+  m24?: number
+  m31?: number
+  m32?: number
+  m33?: number
+  m34?: number
+  m41?: number
+  m42?: number
+  m43?: number
+  m44?: number
+  is2D?: boolean
+}
+export interface DOMPointInit {
+  x?: number
+  y?: number
+  z?: number
+  w?: number
+}
+export interface CanvasRenderingContext2DSettings {
+  alpha?: boolean
+  colorSpace?: 'srgb' | 'display-p3'
+  desynchronized?: boolean
+  willReadFrequently?: boolean
+  pixelFormat?: 'rgba8' | 'rgba16f'
 }
 export const enum LineCap {
-  Butt = 'Butt',
-  Round = 'Round',
-  Square = 'Square'
+  Butt = 'butt',
+  Round = 'round',
+  Square = 'square'
 }
 export const enum LineJoin {
-  Miter = 'Miter',
-  Round = 'Round',
-  Bevel = 'Bevel'
+  Miter = 'miter',
+  Round = 'round',
+  Bevel = 'bevel'
 }
 export const enum TextAlign {
   Start = 'Start',
@@ -66,9 +94,10 @@ export const enum TextBaseline {
   Ideographic = 'Ideographic',
   Bottom = 'Bottom'
 }
-export interface ColorStop {
-  offset: number
-  color: string
+export const enum ImageSmoothingQuality {
+  Low = 'low',
+  Medium = 'medium',
+  High = 'high'
 }
 export const enum RepeatPattern {
   Repeat = 'Repeat',
@@ -76,8 +105,8 @@ export const enum RepeatPattern {
   RepeatY = 'RepeatY',
   NoRepeat = 'NoRepeat'
 }
+export declare function createImageBitmap(image: Canvas | Image | ImageData | ImageBitmap): ImageBitmap
 export declare function version(): string
-export type HTMLCanvas = Canvas
 export declare class Canvas {
   constructor(width: number, height: number)
   get width(): number
@@ -85,10 +114,13 @@ export declare class Canvas {
   get height(): number
   set height(height: number)
   resize(width: number, height: number): void
-  getContext2D(): CanvasRenderingContext2D
+  getContext(contextId: string, attributes?: CanvasRenderingContext2DSettings): CanvasRenderingContext2D | null
+  getContext2D(attributes?: CanvasRenderingContext2DSettings): CanvasRenderingContext2D
   toBuffer(mimeType?: string | undefined | null, quality?: number | undefined | null): Buffer
 }
 export declare class CanvasRenderingContext2D {
+  get canvas(): Canvas
+  getContextAttributes(): CanvasRenderingContext2DSettings
   clearRect(x: number, y: number, width: number, height: number): void
   fillRect(x: number, y: number, width: number, height: number): void
   strokeRect(x: number, y: number, width: number, height: number): void
@@ -100,16 +132,45 @@ export declare class CanvasRenderingContext2D {
   arc(x: number, y: number, radius: number, startAngle: number, endAngle: number, counterClockwise?: boolean | undefined | null): void
   rect(x: number, y: number, width: number, height: number): void
   closePath(): void
-  fill(): void
-  stroke(): void
-  set fillStyle()
-  set fillStyle(value: string)
-  setLinearGradientFillStyle(gradient: LinearGradient): void
-  setRadialGradientFillStyle(gradient: RadialGradient): void
-  get strokeStyle(): string
-  set strokeStyle(value: string)
+  fill(pathOrFillRule?: Path2D | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'): void
+  stroke(path?: Path2D): void
+  clip(pathOrFillRule?: Path2D | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'): void
+  isPointInPath(pathOrX: Path2D | number, xOrY: number, yOrFillRule?: number | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'): boolean
+  isPointInStroke(pathOrX: Path2D | number, xOrY: number, y?: number): boolean
+  get fillStyle(): string | CanvasGradient | CanvasPattern
+  set fillStyle(value: string | CanvasGradient | CanvasPattern)
+  setLinearGradientFillStyle(gradient: CanvasGradient): void
+  setRadialGradientFillStyle(gradient: CanvasGradient): void
+  get strokeStyle(): string | CanvasGradient | CanvasPattern
+  set strokeStyle(value: string | CanvasGradient | CanvasPattern)
   get lineWidth(): number
   set lineWidth(value: number)
+  get globalAlpha(): number
+  set globalAlpha(value: number)
+  get globalCompositeOperation(): 'source-over' | 'source-in' | 'source-out' | 'source-atop' | 'destination-over' | 'destination-in' | 'destination-out' | 'destination-atop' | 'lighter' | 'copy' | 'xor' | 'multiply' | 'screen' | 'overlay' | 'darken' | 'lighten' | 'color-dodge' | 'color-burn' | 'hard-light' | 'soft-light' | 'difference' | 'exclusion' | 'hue' | 'saturation' | 'color' | 'luminosity'
+  set globalCompositeOperation(value: 'source-over' | 'source-in' | 'source-out' | 'source-atop' | 'destination-over' | 'destination-in' | 'destination-out' | 'destination-atop' | 'lighter' | 'copy' | 'xor' | 'multiply' | 'screen' | 'overlay' | 'darken' | 'lighten' | 'color-dodge' | 'color-burn' | 'hard-light' | 'soft-light' | 'difference' | 'exclusion' | 'hue' | 'saturation' | 'color' | 'luminosity')
+  get shadowBlur(): number
+  set shadowBlur(value: number)
+  get shadowColor(): string
+  set shadowColor(value: string)
+  get shadowOffsetX(): number
+  set shadowOffsetX(value: number)
+  get shadowOffsetY(): number
+  set shadowOffsetY(value: number)
+  get imageSmoothingEnabled(): boolean
+  set imageSmoothingEnabled(value: boolean)
+  get lineCap(): 'butt' | 'round' | 'square'
+  set lineCap(value: 'butt' | 'round' | 'square')
+  get lineJoin(): 'miter' | 'round' | 'bevel'
+  set lineJoin(value: 'miter' | 'round' | 'bevel')
+  get miterLimit(): number
+  set miterLimit(value: number)
+  setLineDash(segments: Array<number>): void
+  getLineDash(): Array<number>
+  get lineDashOffset(): number
+  set lineDashOffset(value: number)
+  get imageSmoothingQuality(): 'low' | 'medium' | 'high'
+  set imageSmoothingQuality(value: 'low' | 'medium' | 'high')
   fillText(text: string, x: number, y: number, maxWidth?: number | undefined | null): void
   strokeText(text: string, x: number, y: number, maxWidth?: number | undefined | null): void
   save(): void
@@ -117,23 +178,184 @@ export declare class CanvasRenderingContext2D {
   translate(x: number, y: number): void
   rotate(angle: number): void
   scale(x: number, y: number): void
-  createLinearGradient(x0: number, y0: number, x1: number, y1: number): LinearGradient
-  createRadialGradient(x0: number, y0: number, r0: number, x1: number, y1: number, r1: number): RadialGradient
+  transform(a: number, b: number, c: number, d: number, e: number, f: number): void
+  setTransform(a?: number | DOMMatrix2DInit, b?: number, c?: number, d?: number, e?: number, f?: number): void
+  resetTransform(): void
+  getTransform(): DOMMatrix
+  createLinearGradient(x0: number, y0: number, x1: number, y1: number): CanvasGradient
+  createRadialGradient(x0: number, y0: number, r0: number, x1: number, y1: number, r1: number): CanvasGradient
+  createConicGradient(startAngle: number, x: number, y: number): CanvasGradient
+  createPattern(image: Canvas | Image | ImageData | ImageBitmap, repetition?: string | null): CanvasPattern | null
 }
-export declare class LinearGradient {
-  constructor(x0: number, y0: number, x1: number, y1: number)
+export declare class CanvasGradient {
+  get tileMode(): string
+  set tileMode(value: string)
+  get interpolation(): string | null
+  set interpolation(value?: string | undefined | null)
   addColorStop(offset: number, color: string): void
 }
-export declare class RadialGradient {
-  constructor(x0: number, y0: number, r0: number, x1: number, y1: number, r1: number)
-  addColorStop(offset: number, color: string): void
+export declare class DOMMatrixReadOnly {
+  constructor(init?: string | number[])
+  static fromMatrix(other?: DOMMatrixInit): DOMMatrixReadOnly
+  static fromFloat32Array(array: Float32Array): DOMMatrixReadOnly
+  static fromFloat64Array(array: Float64Array): DOMMatrixReadOnly
+  get a(): number
+  get b(): number
+  get c(): number
+  get d(): number
+  get e(): number
+  get f(): number
+  get m11(): number
+  get m12(): number
+  get m13(): number
+  get m14(): number
+  get m21(): number
+  get m22(): number
+  get m23(): number
+  get m24(): number
+  get m31(): number
+  get m32(): number
+  get m33(): number
+  get m34(): number
+  get m41(): number
+  get m42(): number
+  get m43(): number
+  get m44(): number
+  get is2D(): boolean
+  get isIdentity(): boolean
+  translate(tx?: number | undefined | null, ty?: number | undefined | null, tz?: number | undefined | null): DOMMatrix
+  scale(scaleX?: number | undefined | null, scaleY?: number | undefined | null, scaleZ?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): DOMMatrix
+  scale3d(scale?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): DOMMatrix
+  rotate(rotX?: number | undefined | null, rotY?: number | undefined | null, rotZ?: number | undefined | null): DOMMatrix
+  rotateFromVector(x?: number | undefined | null, y?: number | undefined | null): DOMMatrix
+  rotateAxisAngle(x?: number | undefined | null, y?: number | undefined | null, z?: number | undefined | null, angle?: number | undefined | null): DOMMatrix
+  skewX(sx?: number | undefined | null): DOMMatrix
+  skewY(sy?: number | undefined | null): DOMMatrix
+  multiply(other?: DOMMatrixInit): DOMMatrix
+  flipX(): DOMMatrix
+  flipY(): DOMMatrix
+  inverse(): DOMMatrix
+  transformPoint(point?: DOMPointInit): DOMPoint
+  toFloat32Array(): Float32Array
+  toFloat64Array(): Float64Array
+  toString(): string
+  toJSON(): DOMMatrixInit
+}
+export declare class DOMMatrix {
+  constructor(init?: string | number[])
+  static fromMatrix(other?: DOMMatrixInit): DOMMatrix
+  static fromFloat32Array(array: Float32Array): DOMMatrix
+  static fromFloat64Array(array: Float64Array): DOMMatrix
+  get a(): number
+  set a(value: number)
+  get b(): number
+  set b(value: number)
+  get c(): number
+  set c(value: number)
+  get d(): number
+  set d(value: number)
+  get e(): number
+  set e(value: number)
+  get f(): number
+  set f(value: number)
+  get m11(): number
+  set m11(value: number)
+  get m12(): number
+  set m12(value: number)
+  get m13(): number
+  set m13(value: number)
+  get m14(): number
+  set m14(value: number)
+  get m21(): number
+  set m21(value: number)
+  get m22(): number
+  set m22(value: number)
+  get m23(): number
+  set m23(value: number)
+  get m24(): number
+  set m24(value: number)
+  get m31(): number
+  set m31(value: number)
+  get m32(): number
+  set m32(value: number)
+  get m33(): number
+  set m33(value: number)
+  get m34(): number
+  set m34(value: number)
+  get m41(): number
+  set m41(value: number)
+  get m42(): number
+  set m42(value: number)
+  get m43(): number
+  set m43(value: number)
+  get m44(): number
+  set m44(value: number)
+  get is2D(): boolean
+  get isIdentity(): boolean
+  translate(tx?: number | undefined | null, ty?: number | undefined | null, tz?: number | undefined | null): DOMMatrix
+  scale(scaleX?: number | undefined | null, scaleY?: number | undefined | null, scaleZ?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): DOMMatrix
+  scale3d(scale?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): DOMMatrix
+  rotate(rotX?: number | undefined | null, rotY?: number | undefined | null, rotZ?: number | undefined | null): DOMMatrix
+  rotateFromVector(x?: number | undefined | null, y?: number | undefined | null): DOMMatrix
+  rotateAxisAngle(x?: number | undefined | null, y?: number | undefined | null, z?: number | undefined | null, angle?: number | undefined | null): DOMMatrix
+  skewX(sx?: number | undefined | null): DOMMatrix
+  skewY(sy?: number | undefined | null): DOMMatrix
+  multiply(other?: DOMMatrixInit): DOMMatrix
+  flipX(): DOMMatrix
+  flipY(): DOMMatrix
+  inverse(): DOMMatrix
+  transformPoint(point?: DOMPointInit): DOMPoint
+  toFloat32Array(): Float32Array
+  toFloat64Array(): Float64Array
+  toString(): string
+  toJSON(): DOMMatrixInit
+  multiplySelf(other?: DOMMatrixInit): this
+  preMultiplySelf(other?: DOMMatrixInit): this
+  translateSelf(tx?: number | undefined | null, ty?: number | undefined | null, tz?: number | undefined | null): this
+  scaleSelf(scaleX?: number | undefined | null, scaleY?: number | undefined | null, scaleZ?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): this
+  scale3dSelf(scale?: number | undefined | null, originX?: number | undefined | null, originY?: number | undefined | null, originZ?: number | undefined | null): this
+  rotateSelf(rotX?: number | undefined | null, rotY?: number | undefined | null, rotZ?: number | undefined | null): this
+  rotateFromVectorSelf(x?: number | undefined | null, y?: number | undefined | null): this
+  rotateAxisAngleSelf(x?: number | undefined | null, y?: number | undefined | null, z?: number | undefined | null, angle?: number | undefined | null): this
+  skewXSelf(sx?: number | undefined | null): this
+  skewYSelf(sy?: number | undefined | null): this
+  invertSelf(): this
+  setMatrixValue(transformList: string): this
+}
+export declare class DOMPoint {
+  constructor(x?: number | undefined | null, y?: number | undefined | null, z?: number | undefined | null, w?: number | undefined | null)
+  static fromPoint(other?: DOMPointInit): DOMPoint
+  get x(): number
+  set x(value: number)
+  get y(): number
+  set y(value: number)
+  get z(): number
+  set z(value: number)
+  get w(): number
+  set w(value: number)
+  matrixTransform(matrix?: DOMMatrixInit): DOMPoint
+  toJSON(): DOMPointInit
+}
+export declare class Image {
+  constructor()
+  get src(): Buffer | null
+  set src(value: Buffer)
+  get width(): number
+  get height(): number
+  get complete(): boolean
+}
+export declare class ImageBitmap {
+  get width(): number
+  get height(): number
+  close(): void
 }
 export declare class CanvasPattern {
   constructor(imageData: ImageData, repeatPattern?: string | undefined | null)
+  setTransform(transform?: DOMMatrix2DInit): void
 }
 export declare class Path2D {
   constructor(path?: Path2D | undefined | null)
-  addPath(path: Path2D): void
+  addPath(path: Path2D, transform?: DOMMatrix2DInit): void
   closePath(): void
   moveTo(x: number, y: number): void
   lineTo(x: number, y: number): void
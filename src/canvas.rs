@@ -1,38 +1,139 @@
 use napi::bindgen_prelude::*;
+use napi::JsObject;
 use napi_derive::napi;
-use skia_safe::{Surface, AlphaType, ImageInfo};
-use std::sync::Mutex;
+use skia_safe::{
+  Surface, AlphaType, ColorSpace, ColorType, ImageInfo, named_primaries, named_transfer_fn,
+};
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
 use crate::context_2d::CanvasRenderingContext2D;
 
-#[napi(js_name = "Canvas")]
-pub struct HTMLCanvas {
+// Options for getContext2D(), also returned by ctx.getContextAttributes().
+// pixelFormat is an extension for choosing the backing store precision.
+#[napi(object)]
+#[derive(Clone)]
+pub struct CanvasRenderingContext2DSettings {
+  pub alpha: Option<bool>,
+  #[napi(ts_type = "'srgb' | 'display-p3'")]
+  pub color_space: Option<String>,
+  pub desynchronized: Option<bool>,
+  pub will_read_frequently: Option<bool>,
+  #[napi(ts_type = "'rgba8' | 'rgba16f'")]
+  pub pixel_format: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PredefinedColorSpace {
+  Srgb,
+  DisplayP3,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PixelFormat {
+  Rgba8,
+  Rgba16f,
+}
+
+// The resolved settings, which decide how the surface is created
+#[derive(Clone, Copy, PartialEq)]
+struct ContextAttributes {
+  alpha: bool,
+  color_space: PredefinedColorSpace,
+  desynchronized: bool,
+  will_read_frequently: bool,
+  pixel_format: PixelFormat,
+}
+
+impl Default for ContextAttributes {
+  fn default() -> Self {
+    Self {
+      alpha: true,
+      color_space: PredefinedColorSpace::Srgb,
+      desynchronized: false,
+      will_read_frequently: false,
+      pixel_format: PixelFormat::Rgba8,
+    }
+  }
+}
+
+impl ContextAttributes {
+  fn from_settings(settings: &CanvasRenderingContext2DSettings) -> Result<Self> {
+    let color_space = match settings.color_space.as_deref() {
+      Some("srgb") | None => PredefinedColorSpace::Srgb,
+      Some("display-p3") => PredefinedColorSpace::DisplayP3,
+      Some(other) => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("TypeError: '{}' is not a valid color space", other),
+        ));
+      }
+    };
+
+    let pixel_format = match settings.pixel_format.as_deref() {
+      Some("rgba8") | None => PixelFormat::Rgba8,
+      Some("rgba16f") => PixelFormat::Rgba16f,
+      Some(other) => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("TypeError: '{}' is not a valid pixel format", other),
+        ));
+      }
+    };
+
+    Ok(Self {
+      alpha: settings.alpha.unwrap_or(true),
+      color_space,
+      desynchronized: settings.desynchronized.unwrap_or(false),
+      will_read_frequently: settings.will_read_frequently.unwrap_or(false),
+      pixel_format,
+    })
+  }
+
+  fn to_settings(self) -> CanvasRenderingContext2DSettings {
+    CanvasRenderingContext2DSettings {
+      alpha: Some(self.alpha),
+      color_space: Some(match self.color_space {
+        PredefinedColorSpace::Srgb => "srgb",
+        PredefinedColorSpace::DisplayP3 => "display-p3",
+      }.to_string()),
+      desynchronized: Some(self.desynchronized),
+      will_read_frequently: Some(self.will_read_frequently),
+      pixel_format: Some(match self.pixel_format {
+        PixelFormat::Rgba8 => "rgba8",
+        PixelFormat::Rgba16f => "rgba16f",
+      }.to_string()),
+    }
+  }
+}
+
+#[napi]
+pub struct Canvas {
   width: u32,
   height: u32,
-  surface: Mutex<Surface>,
+  // Shared with the context, which keeps drawing to it even if the canvas is
+  // collected first. Resizing replaces the surface inside the cell.
+  surface: Rc<RefCell<Surface>>,
+  attributes: ContextAttributes,
 }
 
+// Hidden properties linking a canvas object and its context object. Keeping
+// the link on the JS side lets the GC collect the pair once neither is
+// reachable, while either one keeps the other alive.
+const CONTEXT_KEY: &str = "__context";
+pub(crate) const CANVAS_KEY: &str = "__canvas";
+
 #[napi]
-impl HTMLCanvas {
+impl Canvas {
   #[napi(constructor)]
   pub fn new(width: u32, height: u32) -> Result<Self> {
-    let info = ImageInfo::new_n32(
-      (width as i32, height as i32),
-      AlphaType::Premul,
-      None,
-    );
-
-    let mut surface = skia_safe::surfaces::raster(&info, None, None).ok_or_else(|| {
-      Error::new(Status::GenericFailure, "Failed to create Skia surface")
-    })?;
-
-    // Clear the canvas with white background
-    let canvas = surface.canvas();
-    canvas.clear(skia_safe::Color::WHITE);
+    let attributes = ContextAttributes::default();
+    let surface = create_surface(width, height, &attributes)?;
 
     Ok(Self {
       width,
       height,
-      surface: Mutex::new(surface),
+      surface: Rc::new(RefCell::new(surface)),
+      attributes,
     })
   }
 
@@ -42,8 +143,8 @@ impl HTMLCanvas {
   }
 
   #[napi(setter)]
-  pub fn set_width(&mut self, width: u32) -> Result<()> {
-    self.resize(width, self.height)
+  pub fn set_width(&mut self, this: This<JsObject>, width: u32) -> Result<()> {
+    self.resize(this, width, self.height)
   }
 
   #[napi(getter)]
@@ -52,41 +153,94 @@ impl HTMLCanvas {
   }
 
   #[napi(setter)]
-  pub fn set_height(&mut self, height: u32) -> Result<()> {
-    self.resize(self.width, height)
+  pub fn set_height(&mut self, this: This<JsObject>, height: u32) -> Result<()> {
+    self.resize(this, self.width, height)
   }
 
   #[napi]
-  pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
-    let info = ImageInfo::new_n32(
-      (width as i32, height as i32),
-      AlphaType::Premul,
-      None,
-    );
-
-    let mut new_surface = skia_safe::surfaces::raster(&info, None, None).ok_or_else(|| {
-      Error::new(Status::GenericFailure, "Failed to create Skia surface")
-    })?;
-
-    // Clear the canvas with white background
-    let canvas = new_surface.canvas();
-    canvas.clear(skia_safe::Color::WHITE);
+  pub fn resize(&mut self, this: This<JsObject>, width: u32, height: u32) -> Result<()> {
+    // The new surface keeps the attributes chosen by getContext2D()
+    let new_surface = create_surface(width, height, &self.attributes)?;
 
     self.width = width;
     self.height = height;
 
-    let mut surface_guard = self.surface.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock surface mutex")
-    })?;
+    *borrow_surface(&self.surface)? = new_surface;
 
-    *surface_guard = new_surface;
+    // As in browsers, resizing also resets the context to its default state
+    if this.has_own_property(CONTEXT_KEY)? {
+      let mut context: ClassInstance<CanvasRenderingContext2D> = this.get_named_property(CONTEXT_KEY)?;
+      context.reset();
+    }
 
     Ok(())
   }
 
-  #[napi]
-  pub fn get_context_2d(&self) -> Result<CanvasRenderingContext2D> {
-    CanvasRenderingContext2D::new(self)
+  // Returns the same context on every call, or null for context types other
+  // than "2d". The attributes only apply to the first call.
+  #[napi(
+    ts_args_type = "contextId: string, attributes?: CanvasRenderingContext2DSettings",
+    ts_return_type = "CanvasRenderingContext2D | null"
+  )]
+  pub fn get_context(
+    &mut self,
+    env: Env,
+    this: This<JsObject>,
+    context_id: String,
+    attributes: Option<CanvasRenderingContext2DSettings>,
+  ) -> Result<Option<JsObject>> {
+    if context_id != "2d" {
+      return Ok(None);
+    }
+
+    self.context_2d(env, this, attributes).map(Some)
+  }
+
+  // Kept for compatibility, equivalent to getContext("2d")
+  #[napi(
+    ts_args_type = "attributes?: CanvasRenderingContext2DSettings",
+    ts_return_type = "CanvasRenderingContext2D"
+  )]
+  pub fn get_context_2d(
+    &mut self,
+    env: Env,
+    this: This<JsObject>,
+    attributes: Option<CanvasRenderingContext2DSettings>,
+  ) -> Result<JsObject> {
+    self.context_2d(env, this, attributes)
+  }
+
+  fn context_2d(
+    &mut self,
+    env: Env,
+    mut this: JsObject,
+    attributes: Option<CanvasRenderingContext2DSettings>,
+  ) -> Result<JsObject> {
+    if this.has_own_property(CONTEXT_KEY)? {
+      return this.get_named_property(CONTEXT_KEY);
+    }
+
+    // The surface is recreated with the attributes, which is fine as nothing
+    // can have been drawn without a context
+    if let Some(settings) = attributes {
+      let attributes = ContextAttributes::from_settings(&settings)?;
+
+      if attributes != self.attributes {
+        let surface = create_surface(self.width, self.height, &attributes)?;
+
+        *borrow_surface(&self.surface)? = surface;
+
+        self.attributes = attributes;
+      }
+    }
+
+    let context = CanvasRenderingContext2D::new(self.surface.clone(), self.attributes.to_settings());
+    let mut context = context.into_instance(env)?.as_object(env);
+
+    this.define_properties(&[Property::new(CONTEXT_KEY)?.with_value(&context)])?;
+    context.define_properties(&[Property::new(CANVAS_KEY)?.with_value(&this)])?;
+
+    Ok(context)
   }
 
   #[napi]
@@ -101,11 +255,7 @@ impl HTMLCanvas {
     let quality = quality.unwrap_or(0.92);
     let quality_int = (quality * 100.0).clamp(0.0, 100.0) as i32;
 
-    let mut surface_guard = self.surface.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock surface mutex")
-    })?;
-
-    let image = surface_guard.image_snapshot();
+    let image = borrow_surface(&self.surface)?.image_snapshot();
     // Use the encode method with the correct types
     let data = image.encode(None, format, quality_int as u32).ok_or_else(|| {
       Error::new(Status::GenericFailure, "Failed to encode image")
@@ -116,19 +266,52 @@ impl HTMLCanvas {
 
     Ok(buffer)
   }
+
+  // Internal method to copy the current pixels, e.g. for createPattern
+  pub(crate) fn snapshot(&self) -> Result<skia_safe::Image> {
+    Ok(borrow_surface(&self.surface)?.image_snapshot())
+  }
 }
 
-// Internal function to get Skia canvas from HTMLCanvas
-pub(crate) fn get_skia_canvas(canvas: &HTMLCanvas) -> Result<&skia_safe::Canvas> {
-  // In skia-safe 0.84.0, Canvas is no longer Clone
-  // We need to return a reference, but the lifetime is problematic
-  // FIXME: This is a temporary workaround that leaks memory
-  let mut surface_guard = canvas.surface.lock().map_err(|_| {
-    Error::new(Status::GenericFailure, "Failed to lock surface mutex")
+// Creates a surface for the attributes, initialized to transparent black, or
+// opaque black without alpha as in browsers
+fn create_surface(width: u32, height: u32, attributes: &ContextAttributes) -> Result<Surface> {
+  let color_type = match attributes.pixel_format {
+    PixelFormat::Rgba8 => ColorType::N32,
+    PixelFormat::Rgba16f => ColorType::RGBAF16,
+  };
+
+  let alpha_type = if attributes.alpha { AlphaType::Premul } else { AlphaType::Opaque };
+
+  let color_space = match attributes.color_space {
+    PredefinedColorSpace::Srgb => Some(ColorSpace::new_srgb()),
+    PredefinedColorSpace::DisplayP3 => ColorSpace::new_cicp(
+      named_primaries::CicpId::SMPTE_EG_432_1,
+      named_transfer_fn::CicpId::IEC61966_2_1,
+    ),
+  };
+
+  let info = ImageInfo::new(
+    (width as i32, height as i32),
+    color_type,
+    alpha_type,
+    color_space,
+  );
+
+  let mut surface = skia_safe::surfaces::raster(&info, None, None).ok_or_else(|| {
+    Error::new(Status::GenericFailure, "Failed to create Skia surface")
   })?;
 
-  let canvas_ptr = surface_guard.canvas() as *const skia_safe::Canvas;
-  unsafe {
-    Ok(&*canvas_ptr)
-  }
+  let canvas = surface.canvas();
+  canvas.clear(if attributes.alpha { skia_safe::Color::TRANSPARENT } else { skia_safe::Color::BLACK });
+
+  Ok(surface)
+}
+
+// Borrows the surface for the duration of a call. Borrows never outlive a JS
+// call, so this only fails if a draw call re-enters the same surface.
+pub(crate) fn borrow_surface(surface: &RefCell<Surface>) -> Result<RefMut<'_, Surface>> {
+  surface.try_borrow_mut().map_err(|_| {
+    Error::new(Status::GenericFailure, "The canvas surface is already in use")
+  })
 }
@@ -0,0 +1,630 @@
+use skia_safe::Color4f;
+
+// CSS Color Level 4 parsing
+// https://www.w3.org/TR/css-color-4/
+//
+// Every supported syntax is resolved to non-premultiplied extended sRGB, so
+// colors outside the sRGB gamut keep components below 0 or above 1 and can
+// still be drawn to wider gamut surfaces. Only serialization clamps them.
+
+const NAMED_COLORS: &[(&str, u32)] = &[
+  ("aliceblue", 0xf0f8ff),
+  ("antiquewhite", 0xfaebd7),
+  ("aqua", 0x00ffff),
+  ("aquamarine", 0x7fffd4),
+  ("azure", 0xf0ffff),
+  ("beige", 0xf5f5dc),
+  ("bisque", 0xffe4c4),
+  ("black", 0x000000),
+  ("blanchedalmond", 0xffebcd),
+  ("blue", 0x0000ff),
+  ("blueviolet", 0x8a2be2),
+  ("brown", 0xa52a2a),
+  ("burlywood", 0xdeb887),
+  ("cadetblue", 0x5f9ea0),
+  ("chartreuse", 0x7fff00),
+  ("chocolate", 0xd2691e),
+  ("coral", 0xff7f50),
+  ("cornflowerblue", 0x6495ed),
+  ("cornsilk", 0xfff8dc),
+  ("crimson", 0xdc143c),
+  ("cyan", 0x00ffff),
+  ("darkblue", 0x00008b),
+  ("darkcyan", 0x008b8b),
+  ("darkgoldenrod", 0xb8860b),
+  ("darkgray", 0xa9a9a9),
+  ("darkgreen", 0x006400),
+  ("darkgrey", 0xa9a9a9),
+  ("darkkhaki", 0xbdb76b),
+  ("darkmagenta", 0x8b008b),
+  ("darkolivegreen", 0x556b2f),
+  ("darkorange", 0xff8c00),
+  ("darkorchid", 0x9932cc),
+  ("darkred", 0x8b0000),
+  ("darksalmon", 0xe9967a),
+  ("darkseagreen", 0x8fbc8f),
+  ("darkslateblue", 0x483d8b),
+  ("darkslategray", 0x2f4f4f),
+  ("darkslategrey", 0x2f4f4f),
+  ("darkturquoise", 0x00ced1),
+  ("darkviolet", 0x9400d3),
+  ("deeppink", 0xff1493),
+  ("deepskyblue", 0x00bfff),
+  ("dimgray", 0x696969),
+  ("dimgrey", 0x696969),
+  ("dodgerblue", 0x1e90ff),
+  ("firebrick", 0xb22222),
+  ("floralwhite", 0xfffaf0),
+  ("forestgreen", 0x228b22),
+  ("fuchsia", 0xff00ff),
+  ("gainsboro", 0xdcdcdc),
+  ("ghostwhite", 0xf8f8ff),
+  ("gold", 0xffd700),
+  ("goldenrod", 0xdaa520),
+  ("gray", 0x808080),
+  ("green", 0x008000),
+  ("greenyellow", 0xadff2f),
+  ("grey", 0x808080),
+  ("honeydew", 0xf0fff0),
+  ("hotpink", 0xff69b4),
+  ("indianred", 0xcd5c5c),
+  ("indigo", 0x4b0082),
+  ("ivory", 0xfffff0),
+  ("khaki", 0xf0e68c),
+  ("lavender", 0xe6e6fa),
+  ("lavenderblush", 0xfff0f5),
+  ("lawngreen", 0x7cfc00),
+  ("lemonchiffon", 0xfffacd),
+  ("lightblue", 0xadd8e6),
+  ("lightcoral", 0xf08080),
+  ("lightcyan", 0xe0ffff),
+  ("lightgoldenrodyellow", 0xfafad2),
+  ("lightgray", 0xd3d3d3),
+  ("lightgreen", 0x90ee90),
+  ("lightgrey", 0xd3d3d3),
+  ("lightpink", 0xffb6c1),
+  ("lightsalmon", 0xffa07a),
+  ("lightseagreen", 0x20b2aa),
+  ("lightskyblue", 0x87cefa),
+  ("lightslategray", 0x778899),
+  ("lightslategrey", 0x778899),
+  ("lightsteelblue", 0xb0c4de),
+  ("lightyellow", 0xffffe0),
+  ("lime", 0x00ff00),
+  ("limegreen", 0x32cd32),
+  ("linen", 0xfaf0e6),
+  ("magenta", 0xff00ff),
+  ("maroon", 0x800000),
+  ("mediumaquamarine", 0x66cdaa),
+  ("mediumblue", 0x0000cd),
+  ("mediumorchid", 0xba55d3),
+  ("mediumpurple", 0x9370db),
+  ("mediumseagreen", 0x3cb371),
+  ("mediumslateblue", 0x7b68ee),
+  ("mediumspringgreen", 0x00fa9a),
+  ("mediumturquoise", 0x48d1cc),
+  ("mediumvioletred", 0xc71585),
+  ("midnightblue", 0x191970),
+  ("mintcream", 0xf5fffa),
+  ("mistyrose", 0xffe4e1),
+  ("moccasin", 0xffe4b5),
+  ("navajowhite", 0xffdead),
+  ("navy", 0x000080),
+  ("oldlace", 0xfdf5e6),
+  ("olive", 0x808000),
+  ("olivedrab", 0x6b8e23),
+  ("orange", 0xffa500),
+  ("orangered", 0xff4500),
+  ("orchid", 0xda70d6),
+  ("palegoldenrod", 0xeee8aa),
+  ("palegreen", 0x98fb98),
+  ("paleturquoise", 0xafeeee),
+  ("palevioletred", 0xdb7093),
+  ("papayawhip", 0xffefd5),
+  ("peachpuff", 0xffdab9),
+  ("peru", 0xcd853f),
+  ("pink", 0xffc0cb),
+  ("plum", 0xdda0dd),
+  ("powderblue", 0xb0e0e6),
+  ("purple", 0x800080),
+  ("rebeccapurple", 0x663399),
+  ("red", 0xff0000),
+  ("rosybrown", 0xbc8f8f),
+  ("royalblue", 0x4169e1),
+  ("saddlebrown", 0x8b4513),
+  ("salmon", 0xfa8072),
+  ("sandybrown", 0xf4a460),
+  ("seagreen", 0x2e8b57),
+  ("seashell", 0xfff5ee),
+  ("sienna", 0xa0522d),
+  ("silver", 0xc0c0c0),
+  ("skyblue", 0x87ceeb),
+  ("slateblue", 0x6a5acd),
+  ("slategray", 0x708090),
+  ("slategrey", 0x708090),
+  ("snow", 0xfffafa),
+  ("springgreen", 0x00ff7f),
+  ("steelblue", 0x4682b4),
+  ("tan", 0xd2b48c),
+  ("teal", 0x008080),
+  ("thistle", 0xd8bfd8),
+  ("tomato", 0xff6347),
+  ("turquoise", 0x40e0d0),
+  ("violet", 0xee82ee),
+  ("wheat", 0xf5deb3),
+  ("white", 0xffffff),
+  ("whitesmoke", 0xf5f5f5),
+  ("yellow", 0xffff00),
+  ("yellowgreen", 0x9acd32),
+];
+
+// Chromatic adaptation and RGB <-> XYZ matrices from the CSS Color 4 sample code
+const D50_TO_D65: [[f64; 3]; 3] = [
+  [0.955473421488075, -0.02309845494876471, 0.06325924320057072],
+  [-0.0283697093338637, 1.0099953980813041, 0.021041441191917323],
+  [0.012314014864481998, -0.020507649298898964, 1.330365926242124],
+];
+
+const XYZ_D65_TO_LINEAR_SRGB: [[f64; 3]; 3] = [
+  [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+  [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+  [0.05563007969699366, -0.20397695888897652, 1.0569715142428786],
+];
+
+const LINEAR_DISPLAY_P3_TO_XYZ_D65: [[f64; 3]; 3] = [
+  [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+  [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+  [0.0, 0.04511338185890264, 1.043944368900976],
+];
+
+const LINEAR_A98_RGB_TO_XYZ_D65: [[f64; 3]; 3] = [
+  [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+  [0.29734497525053605, 0.6273635662554661, 0.0752914584939978],
+  [0.02703136138641234, 0.07068885253582723, 0.9913375368376388],
+];
+
+const LINEAR_PROPHOTO_RGB_TO_XYZ_D50: [[f64; 3]; 3] = [
+  [0.7977604896723027, 0.13518583717574031, 0.0313493495815248],
+  [0.2880711282292934, 0.7118432178101014, 0.00008565396060525902],
+  [0.0, 0.0, 0.8251046025104601],
+];
+
+const LINEAR_REC2020_TO_XYZ_D65: [[f64; 3]; 3] = [
+  [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+  [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+  [0.0, 0.028072693049087428, 1.060985057710791],
+];
+
+const D50_WHITE: [f64; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+#[derive(Clone, Copy)]
+enum Component {
+  Number(f64),
+  Percentage(f64),
+  // Always in degrees
+  Angle(f64),
+  None,
+}
+
+impl Component {
+  // Resolve a number or percentage, where 100% maps to `percent_reference`
+  fn number(self, percent_reference: f64) -> Option<f64> {
+    match self {
+      Component::Number(v) => Some(v),
+      Component::Percentage(v) => Some(v / 100.0 * percent_reference),
+      Component::None => Some(0.0),
+      Component::Angle(_) => None,
+    }
+  }
+
+  fn hue(self) -> Option<f64> {
+    match self {
+      Component::Number(v) | Component::Angle(v) => Some(v),
+      Component::None => Some(0.0),
+      Component::Percentage(_) => None,
+    }
+  }
+
+  fn alpha(self) -> Option<f64> {
+    self.number(1.0).map(|a| a.clamp(0.0, 1.0))
+  }
+
+  fn is_percentage(self) -> bool {
+    matches!(self, Component::Percentage(_))
+  }
+
+  fn is_none(self) -> bool {
+    matches!(self, Component::None)
+  }
+}
+
+// Parse a CSS <color> string into a non-premultiplied sRGB color.
+// Returns None when the string is not a valid color.
+pub(crate) fn parse_color(value: &str) -> Option<Color4f> {
+  let value = value.trim().to_ascii_lowercase();
+
+  if let Some(hex) = value.strip_prefix('#') {
+    return parse_hex(hex);
+  }
+
+  if let Some(open) = value.find('(') {
+    let body = value[open + 1..].strip_suffix(')')?;
+    return parse_function(&value[..open], body);
+  }
+
+  match value.as_str() {
+    "transparent" => Some(Color4f::new(0.0, 0.0, 0.0, 0.0)),
+    // There is no element to inherit from, so currentcolor resolves to black
+    "currentcolor" => Some(Color4f::new(0.0, 0.0, 0.0, 1.0)),
+    name => {
+      let index = NAMED_COLORS.binary_search_by(|(n, _)| (*n).cmp(name)).ok()?;
+      let rgb = NAMED_COLORS[index].1;
+      Some(from_rgb8(rgb >> 16, (rgb >> 8) & 0xff, rgb & 0xff, 255))
+    }
+  }
+}
+
+fn from_rgb8(r: u32, g: u32, b: u32, a: u32) -> Color4f {
+  Color4f::new(
+    r as f32 / 255.0,
+    g as f32 / 255.0,
+    b as f32 / 255.0,
+    a as f32 / 255.0,
+  )
+}
+
+fn parse_hex(hex: &str) -> Option<Color4f> {
+  if !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+    return None;
+  }
+
+  let digit = |i: usize| u32::from_str_radix(&hex[i..i + 1], 16).ok();
+  let byte = |i: usize| u32::from_str_radix(&hex[i..i + 2], 16).ok();
+
+  match hex.len() {
+    3 | 4 => {
+      let r = digit(0)? * 17;
+      let g = digit(1)? * 17;
+      let b = digit(2)? * 17;
+      let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+      Some(from_rgb8(r, g, b, a))
+    }
+    6 | 8 => {
+      let r = byte(0)?;
+      let g = byte(2)?;
+      let b = byte(4)?;
+      let a = if hex.len() == 8 { byte(6)? } else { 255 };
+      Some(from_rgb8(r, g, b, a))
+    }
+    _ => None,
+  }
+}
+
+fn parse_component(token: &str) -> Option<Component> {
+  if token == "none" {
+    return Some(Component::None);
+  }
+
+  if let Some(number) = token.strip_suffix('%') {
+    return parse_number(number).map(Component::Percentage);
+  }
+
+  // "grad" must be tried before "rad"
+  let units: [(&str, f64); 4] = [
+    ("deg", 1.0),
+    ("grad", 360.0 / 400.0),
+    ("rad", 180.0 / std::f64::consts::PI),
+    ("turn", 360.0),
+  ];
+  for (unit, factor) in units {
+    if let Some(number) = token.strip_suffix(unit) {
+      return parse_number(number).map(|v| Component::Angle(v * factor));
+    }
+  }
+
+  parse_number(token).map(Component::Number)
+}
+
+fn parse_number(token: &str) -> Option<f64> {
+  // Rust also accepts "inf" and "NaN", which are not CSS numbers
+  let valid = !token.is_empty()
+    && token.bytes().all(|c| c.is_ascii_digit() || matches!(c, b'.' | b'+' | b'-' | b'e'))
+    && token.bytes().any(|c| c.is_ascii_digit());
+  if !valid {
+    return None;
+  }
+
+  token.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+struct Arguments {
+  components: Vec<Component>,
+  alpha: Option<Component>,
+  legacy: bool,
+}
+
+fn parse_arguments(body: &str) -> Option<Arguments> {
+  if body.contains(',') {
+    // Legacy comma-separated syntax, where `none` is not allowed
+    let mut components = body
+      .split(',')
+      .map(|token| {
+        let token = token.trim();
+        if token.contains(char::is_whitespace) {
+          return None;
+        }
+        parse_component(token).filter(|c| !c.is_none())
+      })
+      .collect::<Option<Vec<_>>>()?;
+
+    let alpha = match components.len() {
+      3 => None,
+      4 => components.pop(),
+      _ => return None,
+    };
+
+    return Some(Arguments { components, alpha, legacy: true });
+  }
+
+  let (channels, alpha) = match body.split_once('/') {
+    Some((channels, alpha)) => {
+      let alpha = alpha.trim();
+      if alpha.is_empty() || alpha.contains(char::is_whitespace) {
+        return None;
+      }
+      (channels, Some(parse_component(alpha)?))
+    }
+    None => (body, None),
+  };
+
+  let components = channels
+    .split_whitespace()
+    .map(parse_component)
+    .collect::<Option<Vec<_>>>()?;
+
+  Some(Arguments { components, alpha, legacy: false })
+}
+
+fn parse_function(name: &str, body: &str) -> Option<Color4f> {
+  if name == "color" {
+    return parse_color_function(body);
+  }
+
+  let args = parse_arguments(body)?;
+  if args.components.len() != 3 {
+    return None;
+  }
+
+  let alpha = match args.alpha {
+    Some(alpha) => alpha.alpha()?,
+    None => 1.0,
+  };
+  let [c0, c1, c2] = [args.components[0], args.components[1], args.components[2]];
+
+  let rgb = match name {
+    "rgb" | "rgba" => {
+      if args.legacy {
+        // All channels must be numbers, or all must be percentages
+        let percentages = args.components.iter().filter(|c| c.is_percentage()).count();
+        if percentages != 0 && percentages != 3 {
+          return None;
+        }
+      }
+      let channel = |c: Component| c.number(255.0).map(|v| v.clamp(0.0, 255.0) / 255.0);
+      [channel(c0)?, channel(c1)?, channel(c2)?]
+    }
+    "hsl" | "hsla" => {
+      if args.legacy && !(c1.is_percentage() && c2.is_percentage()) {
+        return None;
+      }
+      let s = c1.number(100.0)?.clamp(0.0, 100.0) / 100.0;
+      let l = c2.number(100.0)?.clamp(0.0, 100.0) / 100.0;
+      hsl_to_srgb(c0.hue()?, s, l)
+    }
+    "hwb" if !args.legacy => {
+      let w = c1.number(100.0)? / 100.0;
+      let b = c2.number(100.0)? / 100.0;
+      hwb_to_srgb(c0.hue()?, w, b)
+    }
+    "lab" if !args.legacy => {
+      let l = c0.number(100.0)?.clamp(0.0, 100.0);
+      lab_to_srgb(l, c1.number(125.0)?, c2.number(125.0)?)
+    }
+    "lch" if !args.legacy => {
+      let l = c0.number(100.0)?.clamp(0.0, 100.0);
+      let (a, b) = polar_to_rectangular(c1.number(150.0)?.max(0.0), c2.hue()?);
+      lab_to_srgb(l, a, b)
+    }
+    "oklab" if !args.legacy => {
+      let l = c0.number(1.0)?.clamp(0.0, 1.0);
+      oklab_to_srgb(l, c1.number(0.4)?, c2.number(0.4)?)
+    }
+    "oklch" if !args.legacy => {
+      let l = c0.number(1.0)?.clamp(0.0, 1.0);
+      let (a, b) = polar_to_rectangular(c1.number(0.4)?.max(0.0), c2.hue()?);
+      oklab_to_srgb(l, a, b)
+    }
+    _ => return None,
+  };
+
+  Some(to_color4f(rgb, alpha))
+}
+
+// color(<colorspace> c1 c2 c3 [/ alpha])
+fn parse_color_function(body: &str) -> Option<Color4f> {
+  let body = body.trim_start();
+  let (space, rest) = body.split_at(body.find(char::is_whitespace)?);
+
+  let args = parse_arguments(rest)?;
+  if args.legacy || args.components.len() != 3 {
+    return None;
+  }
+
+  let alpha = match args.alpha {
+    Some(alpha) => alpha.alpha()?,
+    None => 1.0,
+  };
+
+  let mut values = [0.0; 3];
+  for (value, component) in values.iter_mut().zip(args.components.iter()) {
+    *value = component.number(1.0)?;
+  }
+  let transfer = |f: fn(f64) -> f64| values.map(f);
+
+  let rgb = match space {
+    "srgb" => values,
+    "srgb-linear" => values.map(srgb_encode),
+    "display-p3" => xyz_d65_to_srgb(multiply(&LINEAR_DISPLAY_P3_TO_XYZ_D65, transfer(srgb_decode))),
+    "a98-rgb" => xyz_d65_to_srgb(multiply(&LINEAR_A98_RGB_TO_XYZ_D65, transfer(a98_decode))),
+    "prophoto-rgb" => xyz_d50_to_srgb(multiply(&LINEAR_PROPHOTO_RGB_TO_XYZ_D50, transfer(prophoto_decode))),
+    "rec2020" => xyz_d65_to_srgb(multiply(&LINEAR_REC2020_TO_XYZ_D65, transfer(rec2020_decode))),
+    "xyz" | "xyz-d65" => xyz_d65_to_srgb(values),
+    "xyz-d50" => xyz_d50_to_srgb(values),
+    _ => return None,
+  };
+
+  Some(to_color4f(rgb, alpha))
+}
+
+// Out of gamut components are kept as extended sRGB
+fn to_color4f(rgb: [f64; 3], alpha: f64) -> Color4f {
+  let component = |v: f64| if v.is_nan() { 0.0 } else { v as f32 };
+  Color4f::new(component(rgb[0]), component(rgb[1]), component(rgb[2]), alpha as f32)
+}
+
+fn multiply(matrix: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+  matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+fn polar_to_rectangular(chroma: f64, hue: f64) -> (f64, f64) {
+  let radians = hue.to_radians();
+  (chroma * radians.cos(), chroma * radians.sin())
+}
+
+fn hsl_to_srgb(hue: f64, saturation: f64, lightness: f64) -> [f64; 3] {
+  let hue = hue.rem_euclid(360.0);
+  let a = saturation * lightness.min(1.0 - lightness);
+  let f = |n: f64| {
+    let k = (n + hue / 30.0) % 12.0;
+    lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+  };
+  [f(0.0), f(8.0), f(4.0)]
+}
+
+fn hwb_to_srgb(hue: f64, whiteness: f64, blackness: f64) -> [f64; 3] {
+  if whiteness + blackness >= 1.0 {
+    let gray = whiteness / (whiteness + blackness);
+    return [gray; 3];
+  }
+  hsl_to_srgb(hue, 1.0, 0.5).map(|v| v * (1.0 - whiteness - blackness) + whiteness)
+}
+
+fn lab_to_srgb(l: f64, a: f64, b: f64) -> [f64; 3] {
+  const KAPPA: f64 = 24389.0 / 27.0;
+  const EPSILON: f64 = 216.0 / 24389.0;
+
+  let f1 = (l + 16.0) / 116.0;
+  let f0 = a / 500.0 + f1;
+  let f2 = f1 - b / 200.0;
+
+  let x = if f0.powi(3) > EPSILON { f0.powi(3) } else { (116.0 * f0 - 16.0) / KAPPA };
+  let y = if l > KAPPA * EPSILON { f1.powi(3) } else { l / KAPPA };
+  let z = if f2.powi(3) > EPSILON { f2.powi(3) } else { (116.0 * f2 - 16.0) / KAPPA };
+
+  xyz_d50_to_srgb([x * D50_WHITE[0], y * D50_WHITE[1], z * D50_WHITE[2]])
+}
+
+fn oklab_to_srgb(l: f64, a: f64, b: f64) -> [f64; 3] {
+  let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+  let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+  let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+  [
+    4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+    -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+    -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+  ]
+  .map(srgb_encode)
+}
+
+fn xyz_d50_to_srgb(xyz: [f64; 3]) -> [f64; 3] {
+  xyz_d65_to_srgb(multiply(&D50_TO_D65, xyz))
+}
+
+fn xyz_d65_to_srgb(xyz: [f64; 3]) -> [f64; 3] {
+  multiply(&XYZ_D65_TO_LINEAR_SRGB, xyz).map(srgb_encode)
+}
+
+// Transfer functions are extended to negative values by mirroring
+fn srgb_encode(v: f64) -> f64 {
+  let abs = v.abs();
+  if abs <= 0.0031308 {
+    12.92 * v
+  } else {
+    v.signum() * (1.055 * abs.powf(1.0 / 2.4) - 0.055)
+  }
+}
+
+fn srgb_decode(v: f64) -> f64 {
+  let abs = v.abs();
+  if abs <= 0.04045 {
+    v / 12.92
+  } else {
+    v.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn a98_decode(v: f64) -> f64 {
+  v.signum() * v.abs().powf(563.0 / 256.0)
+}
+
+fn prophoto_decode(v: f64) -> f64 {
+  let abs = v.abs();
+  if abs <= 16.0 / 512.0 {
+    v / 16.0
+  } else {
+    v.signum() * abs.powf(1.8)
+  }
+}
+
+fn rec2020_decode(v: f64) -> f64 {
+  const ALPHA: f64 = 1.09929682680944;
+  const BETA: f64 = 0.018053968510807;
+
+  let abs = v.abs();
+  if abs < BETA * 4.5 {
+    v / 4.5
+  } else {
+    v.signum() * ((abs + ALPHA - 1.0) / ALPHA).powf(1.0 / 0.45)
+  }
+}
+
+// Serialize a color the way canvas style getters do: "#rrggbb" when opaque,
+// "rgba(r, g, b, a)" otherwise, clamped to the sRGB gamut
+pub(crate) fn serialize_color(color: &Color4f) -> String {
+  let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+  let (r, g, b, a) = (channel(color.r), channel(color.g), channel(color.b), channel(color.a));
+
+  if a == 255 {
+    return format!("#{:02x}{:02x}{:02x}", r, g, b);
+  }
+
+  format!("rgba({}, {}, {}, {})", r, g, b, serialize_alpha(a))
+}
+
+// The shortest decimal that maps back to the same 8-bit alpha
+fn serialize_alpha(alpha: u8) -> String {
+  if alpha == 0 {
+    return "0".to_string();
+  }
+
+  let value = alpha as f64 / 255.0;
+  for precision in 1..=3 {
+    let rounded = format!("{:.*}", precision, value);
+    if (rounded.parse::<f64>().unwrap_or(0.0) * 255.0).round() as u8 == alpha {
+      return rounded.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+  }
+
+  format!("{:.4}", value).trim_end_matches('0').to_string()
+}
@@ -1,11 +1,22 @@
-use napi::bindgen_prelude::{Either, Error, Result, Status};
+use napi::bindgen_prelude::{Either, Env, Error, Reference, Result, Status, This};
+use napi::JsObject;
 use napi_derive::napi;
 use skia_safe::{
-  Paint, Path, Matrix, Point, Color4f, TextBlob, Font, Shader,
+  Paint, Path, Matrix, Point, Color4f, TextBlob, Font, Surface,
+  CubicResampler, FilterMode, MipmapMode, SamplingOptions,
+  ClipOp, PathFillType, path_utils, PaintCap, PaintJoin, PathEffect, BlendMode,
+  ImageFilter, image_filters, canvas::SaveLayerRec,
 };
-use std::sync::Mutex;
-use crate::canvas::{HTMLCanvas, get_skia_canvas};
-use crate::gradient::{LinearGradient, RadialGradient};
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+use crate::canvas::{CANVAS_KEY, CanvasRenderingContext2DSettings, borrow_surface};
+use crate::color::{parse_color, serialize_color};
+use crate::dom_matrix::{DOMMatrix, DOMMatrix2DInit, matrix_from_components};
+use crate::gradient::CanvasGradient;
+use crate::image::{ImageSource, source_image};
+use crate::path2d::Path2D;
+use crate::pattern::CanvasPattern;
+use crate::style::{CanvasStyle, StyleInput, StyleOutput};
 
 #[napi(object)]
 pub struct TextMetrics {
@@ -23,8 +34,9 @@ pub struct TextMetrics {
   pub ideographic_baseline: f64,
 }
 
-#[napi(string_enum)]
-pub enum CompositeOperation {
+// Not exported, globalCompositeOperation takes and returns the strings
+#[derive(Clone, Copy)]
+enum CompositeOperation {
   SourceOver,
   SourceIn,
   SourceOut,
@@ -47,16 +59,20 @@ pub enum CompositeOperation {
   SoftLight,
   Difference,
   Exclusion,
+  Hue,
+  Saturation,
+  Color,
+  Luminosity,
 }
 
-#[napi(string_enum)]
+#[napi(string_enum = "lowercase")]
 pub enum LineCap {
   Butt,
   Round,
   Square,
 }
 
-#[napi(string_enum)]
+#[napi(string_enum = "lowercase")]
 pub enum LineJoin {
   Miter,
   Round,
@@ -82,82 +98,172 @@ pub enum TextBaseline {
   Bottom,
 }
 
+#[napi(string_enum = "lowercase")]
+pub enum ImageSmoothingQuality {
+  Low,
+  Medium,
+  High,
+}
+
 #[napi]
 pub struct CanvasRenderingContext2D {
-  // Instead of Reference<HTMLCanvas>, store a raw pointer to the HTMLCanvas
-  // This avoids the Reference issues with napi 2.16.17
-  canvas_ptr: *mut HTMLCanvas,
-  fill_style_type: Mutex<FillStyleType>,
-  stroke_style: Mutex<String>,
-  line_width: Mutex<f64>,
-  line_cap: Mutex<LineCap>,
-  line_join: Mutex<LineJoin>,
-  miter_limit: Mutex<f64>,
-  font: Mutex<String>,
-  text_align: Mutex<TextAlign>,
-  text_baseline: Mutex<TextBaseline>,
-  global_alpha: Mutex<f64>,
-  global_composite_operation: Mutex<CompositeOperation>,
-  shadow_blur: Mutex<f64>,
-  shadow_color: Mutex<String>,
-  shadow_offset_x: Mutex<f64>,
-  shadow_offset_y: Mutex<f64>,
-  transform_stack: Mutex<Vec<Matrix>>,
-  current_path: Mutex<Path>,
+  // Shared with the canvas, so drawing doesn't depend on the canvas object
+  // being alive and picks up the new surface after a resize
+  surface: Rc<RefCell<Surface>>,
+  attributes: CanvasRenderingContext2DSettings,
+  state: DrawingState,
+  state_stack: Vec<DrawingState>,
+  // Kept in device space, so changing the transform doesn't move it
+  current_path: Path,
 }
 
-enum FillStyleType {
-    Color(String),
-    LinearGradient(Box<LinearGradient>),
-    RadialGradient(Box<RadialGradient>),
-    Shader(Shader),
-    // Pattern would also be added here in a complete implementation
+// The context attributes, pushed by save() and popped by restore(). The clip
+// is saved alongside it on the Skia canvas. The current path is not part of
+// the drawing state.
+struct DrawingState {
+  transform: Matrix,
+  fill_style: CanvasStyle,
+  stroke_style: CanvasStyle,
+  line_width: f64,
+  line_cap: LineCap,
+  line_join: LineJoin,
+  miter_limit: f64,
+  line_dash: Vec<f64>,
+  line_dash_offset: f64,
+  font: String,
+  text_align: TextAlign,
+  text_baseline: TextBaseline,
+  global_alpha: f64,
+  global_composite_operation: CompositeOperation,
+  shadow_blur: f64,
+  shadow_color: Color4f,
+  shadow_offset_x: f64,
+  shadow_offset_y: f64,
+  image_smoothing_enabled: bool,
+  image_smoothing_quality: ImageSmoothingQuality,
 }
 
-#[napi]
-impl CanvasRenderingContext2D {
-  pub fn new(canvas: &HTMLCanvas) -> Result<Self> {
-    // Store as a raw pointer without attempting any transmute
-    let canvas_ptr = canvas as *const HTMLCanvas as usize as *mut HTMLCanvas;
+impl DrawingState {
+  fn new() -> Self {
+    Self {
+      transform: Matrix::default(),
+      fill_style: CanvasStyle::Color(Color4f::new(0.0, 0.0, 0.0, 1.0)),
+      stroke_style: CanvasStyle::Color(Color4f::new(0.0, 0.0, 0.0, 1.0)),
+      line_width: 1.0,
+      line_cap: LineCap::Butt,
+      line_join: LineJoin::Miter,
+      miter_limit: 10.0,
+      line_dash: Vec::new(),
+      line_dash_offset: 0.0,
+      font: String::from("10px sans-serif"),
+      text_align: TextAlign::Start,
+      text_baseline: TextBaseline::Alphabetic,
+      global_alpha: 1.0,
+      global_composite_operation: CompositeOperation::SourceOver,
+      shadow_blur: 0.0,
+      shadow_color: Color4f::new(0.0, 0.0, 0.0, 0.0),
+      shadow_offset_x: 0.0,
+      shadow_offset_y: 0.0,
+      image_smoothing_enabled: true,
+      image_smoothing_quality: ImageSmoothingQuality::Low,
+    }
+  }
 
+  // Styles need the env to share their gradient or pattern
+  fn clone_with_env(&self, env: Env) -> Result<Self> {
     Ok(Self {
-        canvas_ptr,
-        fill_style_type: Mutex::new(FillStyleType::Color("black".to_string())),
-        stroke_style: Mutex::new(String::from("black")),
-        line_width: Mutex::new(1.0),
-        line_cap: Mutex::new(LineCap::Butt),
-        line_join: Mutex::new(LineJoin::Miter),
-        miter_limit: Mutex::new(10.0),
-        font: Mutex::new(String::from("10px sans-serif")),
-        text_align: Mutex::new(TextAlign::Start),
-        text_baseline: Mutex::new(TextBaseline::Alphabetic),
-        global_alpha: Mutex::new(1.0),
-        global_composite_operation: Mutex::new(CompositeOperation::SourceOver),
-        shadow_blur: Mutex::new(0.0),
-        shadow_color: Mutex::new(String::from("rgba(0,0,0,0)")),
-        shadow_offset_x: Mutex::new(0.0),
-        shadow_offset_y: Mutex::new(0.0),
-        transform_stack: Mutex::new(vec![Matrix::default()]),
-        current_path: Mutex::new(Path::new()),
+      transform: self.transform,
+      fill_style: self.fill_style.clone_with_env(env)?,
+      stroke_style: self.stroke_style.clone_with_env(env)?,
+      line_width: self.line_width,
+      line_cap: self.line_cap,
+      line_join: self.line_join,
+      miter_limit: self.miter_limit,
+      line_dash: self.line_dash.clone(),
+      line_dash_offset: self.line_dash_offset,
+      font: self.font.clone(),
+      text_align: self.text_align,
+      text_baseline: self.text_baseline,
+      global_alpha: self.global_alpha,
+      global_composite_operation: self.global_composite_operation,
+      shadow_blur: self.shadow_blur,
+      shadow_color: self.shadow_color,
+      shadow_offset_x: self.shadow_offset_x,
+      shadow_offset_y: self.shadow_offset_y,
+      image_smoothing_enabled: self.image_smoothing_enabled,
+      image_smoothing_quality: self.image_smoothing_quality,
     })
   }
+}
+
+#[napi]
+impl CanvasRenderingContext2D {
+  pub fn new(
+    surface: Rc<RefCell<Surface>>,
+    attributes: CanvasRenderingContext2DSettings,
+  ) -> Self {
+    Self {
+      surface,
+      attributes,
+      state: DrawingState::new(),
+      state_stack: Vec::new(),
+      current_path: Path::new(),
+    }
+  }
+
+  // Called when the canvas is resized. The Skia save stack went away with
+  // the old surface, so only the saved states and the path are left.
+  pub(crate) fn reset(&mut self) {
+    self.state = DrawingState::new();
+    self.state_stack.clear();
+    self.current_path = Path::new();
+  }
+
+  // The canvas this context draws to, linked by getContext()
+  #[napi(getter, ts_return_type = "Canvas")]
+  pub fn canvas(&self, this: This<JsObject>) -> Result<JsObject> {
+    this.get_named_property(CANVAS_KEY)
+  }
+
+  // The attributes the canvas was set up with
+  #[napi]
+  pub fn get_context_attributes(&self) -> CanvasRenderingContext2DSettings {
+    self.attributes.clone()
+  }
 
   // Basic drawing methods
 
   #[napi]
-  pub fn clear_rect(&self, _x: f64, _y: f64, _width: f64, _height: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
-    // Clear by drawing a transparent rectangle over the area
-    canvas.clear(skia_safe::Color4f::new(0.0, 0.0, 0.0, 0.0));
+  pub fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Result<()> {
+    // Non-finite arguments are ignored
+    if ![x, y, width, height].iter().all(|v| v.is_finite()) {
+      return Ok(());
+    }
+
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
+    let rect = skia_safe::Rect::new(
+      x as f32,
+      y as f32,
+      (x + width) as f32,
+      (y + height) as f32
+    );
+
+    // Clear rather than a transparent fill, so the pixels become transparent
+    // black under the transform and clip. Shadows, globalAlpha and
+    // globalCompositeOperation don't apply.
+    let mut paint = Paint::default();
+    paint.set_blend_mode(BlendMode::Clear);
+
+    canvas.draw_rect(rect, &paint);
 
     Ok(())
   }
 
   #[napi]
   pub fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
     let rect = skia_safe::Rect::new(
       x as f32,
       y as f32,
@@ -165,42 +271,21 @@ impl CanvasRenderingContext2D {
       (y + height) as f32
     );
 
-    let fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
-
     let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
     paint.set_style(skia_safe::PaintStyle::Fill);
+    self.apply_fill_style(&mut paint)?;
 
-    // Set the shader based on fill style
-    match &*fill_style_type {
-      FillStyleType::Color(_color) => {
-        // For simplicity, just use black
-        // In a complete implementation, you'd parse the color string
-        paint.set_color4f(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-      },
-      FillStyleType::LinearGradient(gradient) => {
-        let shader = gradient.create_shader()?;
-        paint.set_shader(shader);
-      },
-      FillStyleType::RadialGradient(gradient) => {
-        let shader = gradient.create_shader()?;
-        paint.set_shader(shader);
-      },
-      FillStyleType::Shader(shader) => {
-        paint.set_shader(shader.clone());
-      },
-    }
-
-    canvas.draw_rect(rect, &paint);
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_rect(rect, paint);
+    });
 
     Ok(())
   }
 
   #[napi]
   pub fn stroke_rect(&self, x: f64, y: f64, width: f64, height: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
     let rect = skia_safe::Rect::new(
       x as f32,
       y as f32,
@@ -208,18 +293,12 @@ impl CanvasRenderingContext2D {
       (y + height) as f32
     );
 
-    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-    paint.set_style(skia_safe::PaintStyle::Stroke);
-
-    let line_width = *self.line_width.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock line_width mutex")
-    })?;
+    let mut paint = self.stroke_paint();
+    self.apply_stroke_style(&mut paint)?;
 
-    paint.set_stroke_width(line_width as f32);
-
-    // TODO: Parse stroke_style to determine color or pattern or gradient
-
-    canvas.draw_rect(rect, &paint);
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_rect(rect, paint);
+    });
 
     Ok(())
   }
@@ -227,73 +306,36 @@ impl CanvasRenderingContext2D {
   // Path methods
 
   #[napi]
-  pub fn begin_path(&self) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    *current_path = Path::new();
-
-    Ok(())
+  pub fn begin_path(&mut self) {
+    self.current_path = Path::new();
   }
 
   #[napi]
-  pub fn move_to(&self, x: f64, y: f64) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    current_path.move_to(Point::new(x as f32, y as f32));
-
-    Ok(())
+  pub fn move_to(&mut self, x: f64, y: f64) {
+    let point = self.device_point(x, y);
+    self.current_path.move_to(point);
   }
 
   #[napi]
-  pub fn line_to(&self, x: f64, y: f64) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    current_path.line_to(Point::new(x as f32, y as f32));
-
-    Ok(())
+  pub fn line_to(&mut self, x: f64, y: f64) {
+    let point = self.device_point(x, y);
+    self.current_path.line_to(point);
   }
 
   #[napi]
-  pub fn bezier_curve_to(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    current_path.cubic_to(
-      Point::new(cp1x as f32, cp1y as f32),
-      Point::new(cp2x as f32, cp2y as f32),
-      Point::new(x as f32, y as f32),
-    );
-
-    Ok(())
+  pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+    let (cp1, cp2, point) = (self.device_point(cp1x, cp1y), self.device_point(cp2x, cp2y), self.device_point(x, y));
+    self.current_path.cubic_to(cp1, cp2, point);
   }
 
   #[napi]
-  pub fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    current_path.quad_to(
-      Point::new(cpx as f32, cpy as f32),
-      Point::new(x as f32, y as f32),
-    );
-
-    Ok(())
+  pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
+    let (cp, point) = (self.device_point(cpx, cpy), self.device_point(x, y));
+    self.current_path.quad_to(cp, point);
   }
 
   #[napi]
-  pub fn arc(&self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64, counter_clockwise: Option<bool>) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
+  pub fn arc(&mut self, x: f64, y: f64, radius: f64, start_angle: f64, end_angle: f64, counter_clockwise: Option<bool>) {
     let ccw = counter_clockwise.unwrap_or(false);
 
     // Skia uses a rectangle and start/sweep angles for arcs
@@ -320,18 +362,15 @@ impl CanvasRenderingContext2D {
         (y + radius * f64::sin(start_angle)) as f32
     );
 
-    current_path.add_arc(rect, start_deg, sweep_deg);
-
-    Ok(())
+    let mut arc = Path::new();
+    arc.add_arc(rect, start_deg, sweep_deg);
+    self.add_to_current_path(&arc);
   }
 
   #[napi]
-  pub fn rect(&self, x: f64, y: f64, width: f64, height: f64) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
-
-    current_path.add_rect(
+  pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) {
+    let mut rect = Path::new();
+    rect.add_rect(
       skia_safe::Rect::new(
         x as f32,
         y as f32,
@@ -340,182 +379,446 @@ impl CanvasRenderingContext2D {
       ),
       None,
     );
+    self.add_to_current_path(&rect);
+  }
+
+  #[napi]
+  pub fn close_path(&mut self) {
+    self.current_path.close();
+  }
+
+  // Either fill(fillRule?) for the current path or fill(path, fillRule?)
+  #[napi(ts_args_type = "pathOrFillRule?: Path2D | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'")]
+  pub fn fill(&self, path_or_fill_rule: Option<Either<&Path2D, String>>, fill_rule: Option<String>) -> Result<()> {
+    let (path, fill_type) = split_fill_rule_args(path_or_fill_rule, fill_rule)?;
+
+    let Some(mut path) = self.user_path(path) else {
+      return Ok(());
+    };
+    path.set_fill_type(fill_type);
+
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
+
+    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
+    paint.set_style(skia_safe::PaintStyle::Fill);
+    self.apply_fill_style(&mut paint)?;
+
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_path(&path, paint);
+    });
 
     Ok(())
   }
 
-  #[napi]
-  pub fn close_path(&self) -> Result<()> {
-    let mut current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
+  // Strokes the given path, or the current path when omitted
+  #[napi(ts_args_type = "path?: Path2D")]
+  pub fn stroke(&self, path: Option<&Path2D>) -> Result<()> {
+    let Some(path) = self.user_path(path) else {
+      return Ok(());
+    };
+
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
 
-    current_path.close();
+    let mut paint = self.stroke_paint();
+    self.apply_stroke_style(&mut paint)?;
+
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_path(&path, paint);
+    });
 
     Ok(())
   }
 
-  #[napi]
-  pub fn fill(&self) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+  // Either clip(fillRule?) for the current path or clip(path, fillRule?).
+  // The clip intersects with the current one and is restored by restore().
+  #[napi(ts_args_type = "pathOrFillRule?: Path2D | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'")]
+  pub fn clip(&self, path_or_fill_rule: Option<Either<&Path2D, String>>, fill_rule: Option<String>) -> Result<()> {
+    let (path, fill_type) = split_fill_rule_args(path_or_fill_rule, fill_rule)?;
 
-    let current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
+    let mut path = self.device_path(path);
+    path.set_fill_type(fill_type);
 
-    let fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
+    // The clip is applied in device space, where the path already is
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
+    canvas.reset_matrix();
+    canvas.clip_path(&path, ClipOp::Intersect, true);
 
-    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-    paint.set_style(skia_safe::PaintStyle::Fill);
+    Ok(())
+  }
 
-    // Set the shader based on fill style
-    match &*fill_style_type {
-      FillStyleType::Color(_color) => {
-        // For simplicity, just use black
-        // In a complete implementation, you'd parse the color string
-        paint.set_color4f(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-      },
-      FillStyleType::LinearGradient(gradient) => {
-        let shader = gradient.create_shader()?;
-        paint.set_shader(shader);
-      },
-      FillStyleType::RadialGradient(gradient) => {
-        let shader = gradient.create_shader()?;
-        paint.set_shader(shader);
-      },
-      FillStyleType::Shader(shader) => {
-        paint.set_shader(shader.clone());
-      },
+  // Hit testing
+
+  // Either isPointInPath(x, y, fillRule?) for the current path or
+  // isPointInPath(path, x, y, fillRule?). The point is in canvas
+  // coordinates, while a given path is mapped by the current transform.
+  #[napi(ts_args_type = "pathOrX: Path2D | number, xOrY: number, yOrFillRule?: number | 'nonzero' | 'evenodd', fillRule?: 'nonzero' | 'evenodd'")]
+  pub fn is_point_in_path(
+    &self,
+    path_or_x: Either<&Path2D, f64>,
+    x_or_y: f64,
+    y_or_fill_rule: Option<Either<f64, String>>,
+    fill_rule: Option<String>,
+  ) -> Result<bool> {
+    let (path, x, y, fill_rule) = match (path_or_x, y_or_fill_rule) {
+      (Either::A(path), Some(Either::A(y))) => (Some(path), x_or_y, y, fill_rule),
+      (Either::B(x), Some(Either::B(fill_rule))) => (None, x, x_or_y, Some(fill_rule)),
+      (Either::B(x), None) => (None, x, x_or_y, None),
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "TypeError: isPointInPath() takes (x, y, fillRule?) or (path, x, y, fillRule?)",
+        ));
+      }
+    };
+
+    let fill_type = parse_fill_rule(fill_rule.as_deref())?;
+
+    if !x.is_finite() || !y.is_finite() {
+      return Ok(false);
     }
 
-    canvas.draw_path(&current_path, &paint);
+    let mut path = self.device_path(path);
+    path.set_fill_type(fill_type);
 
-    Ok(())
+    Ok(path.contains(Point::new(x as f32, y as f32)))
   }
 
-  #[napi]
-  pub fn stroke(&self) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+  // Either isPointInStroke(x, y) for the current path or
+  // isPointInStroke(path, x, y), using the current line settings
+  #[napi(ts_args_type = "pathOrX: Path2D | number, xOrY: number, y?: number")]
+  pub fn is_point_in_stroke(&self, path_or_x: Either<&Path2D, f64>, x_or_y: f64, y: Option<f64>) -> Result<bool> {
+    let (path, x, y) = match (path_or_x, y) {
+      (Either::A(path), Some(y)) => (Some(path), x_or_y, y),
+      (Either::B(x), _) => (None, x, x_or_y),
+      _ => {
+        return Err(Error::new(
+          Status::InvalidArg,
+          "TypeError: isPointInStroke() takes (x, y) or (path, x, y)",
+        ));
+      }
+    };
+
+    if !x.is_finite() || !y.is_finite() {
+      return Ok(false);
+    }
 
-    let current_path = self.current_path.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock current_path mutex")
-    })?;
+    let Some(path) = self.user_path(path) else {
+      return Ok(false);
+    };
 
-    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-    paint.set_style(skia_safe::PaintStyle::Stroke);
+    // The outline is computed in user space, so the line width is scaled by
+    // the transform like when stroking
+    let transform = self.state.transform;
+    let mut outline = Path::new();
 
-    let line_width = *self.line_width.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock line_width mutex")
-    })?;
+    if !path_utils::fill_path_with_paint(&path, &self.stroke_paint(), &mut outline, None, transform) {
+      return Ok(false);
+    }
+
+    Ok(outline.with_transform(&transform).contains(Point::new(x as f32, y as f32)))
+  }
 
-    paint.set_stroke_width(line_width as f32);
+  // Properties
 
-    // TODO: Parse stroke_style to determine color or pattern or gradient
+  #[napi(getter, ts_return_type = "string | CanvasGradient | CanvasPattern")]
+  pub fn fill_style(&self, env: Env) -> Result<StyleOutput> {
+    self.state.fill_style.to_output(env)
+  }
 
-    canvas.draw_path(&current_path, &paint);
+  #[napi(setter, ts_args_type = "value: string | CanvasGradient | CanvasPattern")]
+  pub fn set_fill_style(&mut self, env: Env, value: StyleInput) -> Result<()> {
+    // Invalid colors are ignored, as in browsers
+    if let Some(style) = CanvasStyle::from_input(env, value)? {
+      self.state.fill_style = style;
+    }
 
     Ok(())
   }
 
-  // Properties
+  // Kept for compatibility, assigning the gradient to fillStyle is equivalent
+  #[napi(js_name = "setLinearGradientFillStyle")]
+  pub fn set_linear_gradient_fill_style(&mut self, gradient: Reference<CanvasGradient>) {
+    self.state.fill_style = CanvasStyle::Gradient(gradient);
+  }
 
-  #[napi(getter, setter)]
-  pub fn fill_style(&self) -> Result<String> {
-    let fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
+  #[napi(js_name = "setRadialGradientFillStyle")]
+  pub fn set_radial_gradient_fill_style(&mut self, gradient: Reference<CanvasGradient>) {
+    self.state.fill_style = CanvasStyle::Gradient(gradient);
+  }
 
-    Ok(match *fill_style_type {
-      FillStyleType::Color(ref color) => color.clone(),
-      FillStyleType::LinearGradient(_) => "linear-gradient".to_string(),
-      FillStyleType::RadialGradient(_) => "radial-gradient".to_string(),
-      FillStyleType::Shader(_) => "shader".to_string(),
-    })
+  #[napi(getter, ts_return_type = "string | CanvasGradient | CanvasPattern")]
+  pub fn stroke_style(&self, env: Env) -> Result<StyleOutput> {
+    self.state.stroke_style.to_output(env)
+  }
+
+  #[napi(setter, ts_args_type = "value: string | CanvasGradient | CanvasPattern")]
+  pub fn set_stroke_style(&mut self, env: Env, value: StyleInput) -> Result<()> {
+    // Invalid colors are ignored, as in browsers
+    if let Some(style) = CanvasStyle::from_input(env, value)? {
+      self.state.stroke_style = style;
+    }
+
+    Ok(())
+  }
+
+  #[napi(getter)]
+  pub fn line_width(&self) -> f64 {
+    self.state.line_width
   }
 
   #[napi(setter)]
-  pub fn set_fill_style(&self, value: String) -> Result<()> {
-    let mut fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
+  pub fn set_line_width(&mut self, value: f64) {
+    self.state.line_width = value;
+  }
 
-    *fill_style_type = FillStyleType::Color(value);
+  #[napi(getter, ts_return_type = "'butt' | 'round' | 'square'")]
+  pub fn line_cap(&self) -> String {
+    match self.state.line_cap {
+      LineCap::Butt => "butt",
+      LineCap::Round => "round",
+      LineCap::Square => "square",
+    }.to_string()
+  }
 
-    Ok(())
+  #[napi(setter, ts_args_type = "value: 'butt' | 'round' | 'square'")]
+  pub fn set_line_cap(&mut self, value: String) {
+    // Invalid values are ignored
+    self.state.line_cap = match value.as_str() {
+      "butt" => LineCap::Butt,
+      "round" => LineCap::Round,
+      "square" => LineCap::Square,
+      _ => return,
+    };
   }
 
-  #[napi(js_name = "setLinearGradientFillStyle")]
-  pub fn set_linear_gradient_fill_style(&self, gradient: &LinearGradient) -> Result<()> {
-    let mut fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
+  #[napi(getter, ts_return_type = "'miter' | 'round' | 'bevel'")]
+  pub fn line_join(&self) -> String {
+    match self.state.line_join {
+      LineJoin::Miter => "miter",
+      LineJoin::Round => "round",
+      LineJoin::Bevel => "bevel",
+    }.to_string()
+  }
 
-    // Create a shader directly from the gradient
-    let shader = gradient.create_shader()?;
+  #[napi(setter, ts_args_type = "value: 'miter' | 'round' | 'bevel'")]
+  pub fn set_line_join(&mut self, value: String) {
+    // Invalid values are ignored
+    self.state.line_join = match value.as_str() {
+      "miter" => LineJoin::Miter,
+      "round" => LineJoin::Round,
+      "bevel" => LineJoin::Bevel,
+      _ => return,
+    };
+  }
 
-    // Store the shader in FillStyleType
-    *fill_style_type = FillStyleType::Shader(shader);
+  #[napi(getter)]
+  pub fn miter_limit(&self) -> f64 {
+    self.state.miter_limit
+  }
 
-    Ok(())
+  #[napi(setter)]
+  pub fn set_miter_limit(&mut self, value: f64) {
+    // Zero, negative and non-finite values are ignored
+    if value.is_finite() && value > 0.0 {
+      self.state.miter_limit = value;
+    }
   }
 
-  #[napi(js_name = "setRadialGradientFillStyle")]
-  pub fn set_radial_gradient_fill_style(&self, gradient: &RadialGradient) -> Result<()> {
-    let mut fill_style_type = self.fill_style_type.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock fill_style_type mutex")
-    })?;
+  // Segment lists with negative or non-finite values are ignored, and odd
+  // lengths are repeated to make them even
+  #[napi]
+  pub fn set_line_dash(&mut self, segments: Vec<f64>) {
+    if !segments.iter().all(|v| v.is_finite() && *v >= 0.0) {
+      return;
+    }
 
-    // Create a shader directly from the gradient
-    let shader = gradient.create_shader()?;
+    self.state.line_dash = if segments.len() % 2 == 1 {
+      segments.repeat(2)
+    } else {
+      segments
+    };
+  }
 
-    // Store the shader in FillStyleType
-    *fill_style_type = FillStyleType::Shader(shader);
+  #[napi]
+  pub fn get_line_dash(&self) -> Vec<f64> {
+    self.state.line_dash.clone()
+  }
 
-    Ok(())
+  #[napi(getter)]
+  pub fn line_dash_offset(&self) -> f64 {
+    self.state.line_dash_offset
+  }
+
+  #[napi(setter)]
+  pub fn set_line_dash_offset(&mut self, value: f64) {
+    // Non-finite values are ignored
+    if value.is_finite() {
+      self.state.line_dash_offset = value;
+    }
   }
 
   #[napi(getter)]
-  pub fn stroke_style(&self) -> Result<String> {
-    let stroke_style = self.stroke_style.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock stroke_style mutex")
-    })?;
+  pub fn global_alpha(&self) -> f64 {
+    self.state.global_alpha
+  }
 
-    Ok(stroke_style.clone())
+  #[napi(setter)]
+  pub fn set_global_alpha(&mut self, value: f64) {
+    // Values outside [0, 1] and non-finite values are ignored
+    if value.is_finite() && (0.0..=1.0).contains(&value) {
+      self.state.global_alpha = value;
+    }
+  }
+
+  #[napi(getter, ts_return_type = "'source-over' | 'source-in' | 'source-out' | 'source-atop' | 'destination-over' | 'destination-in' | 'destination-out' | 'destination-atop' | 'lighter' | 'copy' | 'xor' | 'multiply' | 'screen' | 'overlay' | 'darken' | 'lighten' | 'color-dodge' | 'color-burn' | 'hard-light' | 'soft-light' | 'difference' | 'exclusion' | 'hue' | 'saturation' | 'color' | 'luminosity'")]
+  pub fn global_composite_operation(&self) -> String {
+    match self.state.global_composite_operation {
+      CompositeOperation::SourceOver => "source-over",
+      CompositeOperation::SourceIn => "source-in",
+      CompositeOperation::SourceOut => "source-out",
+      CompositeOperation::SourceAtop => "source-atop",
+      CompositeOperation::DestinationOver => "destination-over",
+      CompositeOperation::DestinationIn => "destination-in",
+      CompositeOperation::DestinationOut => "destination-out",
+      CompositeOperation::DestinationAtop => "destination-atop",
+      CompositeOperation::Lighter => "lighter",
+      CompositeOperation::Copy => "copy",
+      CompositeOperation::Xor => "xor",
+      CompositeOperation::Multiply => "multiply",
+      CompositeOperation::Screen => "screen",
+      CompositeOperation::Overlay => "overlay",
+      CompositeOperation::Darken => "darken",
+      CompositeOperation::Lighten => "lighten",
+      CompositeOperation::ColorDodge => "color-dodge",
+      CompositeOperation::ColorBurn => "color-burn",
+      CompositeOperation::HardLight => "hard-light",
+      CompositeOperation::SoftLight => "soft-light",
+      CompositeOperation::Difference => "difference",
+      CompositeOperation::Exclusion => "exclusion",
+      CompositeOperation::Hue => "hue",
+      CompositeOperation::Saturation => "saturation",
+      CompositeOperation::Color => "color",
+      CompositeOperation::Luminosity => "luminosity",
+    }.to_string()
+  }
+
+  #[napi(setter, ts_args_type = "value: 'source-over' | 'source-in' | 'source-out' | 'source-atop' | 'destination-over' | 'destination-in' | 'destination-out' | 'destination-atop' | 'lighter' | 'copy' | 'xor' | 'multiply' | 'screen' | 'overlay' | 'darken' | 'lighten' | 'color-dodge' | 'color-burn' | 'hard-light' | 'soft-light' | 'difference' | 'exclusion' | 'hue' | 'saturation' | 'color' | 'luminosity'")]
+  pub fn set_global_composite_operation(&mut self, value: String) {
+    // Invalid values are ignored
+    self.state.global_composite_operation = match value.as_str() {
+      "source-over" => CompositeOperation::SourceOver,
+      "source-in" => CompositeOperation::SourceIn,
+      "source-out" => CompositeOperation::SourceOut,
+      "source-atop" => CompositeOperation::SourceAtop,
+      "destination-over" => CompositeOperation::DestinationOver,
+      "destination-in" => CompositeOperation::DestinationIn,
+      "destination-out" => CompositeOperation::DestinationOut,
+      "destination-atop" => CompositeOperation::DestinationAtop,
+      "lighter" => CompositeOperation::Lighter,
+      "copy" => CompositeOperation::Copy,
+      "xor" => CompositeOperation::Xor,
+      "multiply" => CompositeOperation::Multiply,
+      "screen" => CompositeOperation::Screen,
+      "overlay" => CompositeOperation::Overlay,
+      "darken" => CompositeOperation::Darken,
+      "lighten" => CompositeOperation::Lighten,
+      "color-dodge" => CompositeOperation::ColorDodge,
+      "color-burn" => CompositeOperation::ColorBurn,
+      "hard-light" => CompositeOperation::HardLight,
+      "soft-light" => CompositeOperation::SoftLight,
+      "difference" => CompositeOperation::Difference,
+      "exclusion" => CompositeOperation::Exclusion,
+      "hue" => CompositeOperation::Hue,
+      "saturation" => CompositeOperation::Saturation,
+      "color" => CompositeOperation::Color,
+      "luminosity" => CompositeOperation::Luminosity,
+      _ => return,
+    };
+  }
+
+  #[napi(getter)]
+  pub fn shadow_blur(&self) -> f64 {
+    self.state.shadow_blur
   }
 
   #[napi(setter)]
-  pub fn set_stroke_style(&self, value: String) -> Result<()> {
-    let mut stroke_style = self.stroke_style.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock stroke_style mutex")
-    })?;
+  pub fn set_shadow_blur(&mut self, value: f64) {
+    // Negative and non-finite values are ignored
+    if value.is_finite() && value >= 0.0 {
+      self.state.shadow_blur = value;
+    }
+  }
 
-    *stroke_style = value;
+  #[napi(getter)]
+  pub fn shadow_color(&self) -> String {
+    serialize_color(&self.state.shadow_color)
+  }
 
-    Ok(())
+  #[napi(setter)]
+  pub fn set_shadow_color(&mut self, value: String) {
+    // Invalid colors are ignored
+    if let Some(color) = parse_color(&value) {
+      self.state.shadow_color = color;
+    }
   }
 
   #[napi(getter)]
-  pub fn line_width(&self) -> Result<f64> {
-    let line_width = self.line_width.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock line_width mutex")
-    })?;
+  pub fn shadow_offset_x(&self) -> f64 {
+    self.state.shadow_offset_x
+  }
 
-    Ok(*line_width)
+  #[napi(setter)]
+  pub fn set_shadow_offset_x(&mut self, value: f64) {
+    // Non-finite values are ignored
+    if value.is_finite() {
+      self.state.shadow_offset_x = value;
+    }
+  }
+
+  #[napi(getter)]
+  pub fn shadow_offset_y(&self) -> f64 {
+    self.state.shadow_offset_y
   }
 
   #[napi(setter)]
-  pub fn set_line_width(&self, value: f64) -> Result<()> {
-    let mut line_width = self.line_width.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock line_width mutex")
-    })?;
+  pub fn set_shadow_offset_y(&mut self, value: f64) {
+    // Non-finite values are ignored
+    if value.is_finite() {
+      self.state.shadow_offset_y = value;
+    }
+  }
 
-    *line_width = value;
+  #[napi(getter)]
+  pub fn image_smoothing_enabled(&self) -> bool {
+    self.state.image_smoothing_enabled
+  }
 
-    Ok(())
+  #[napi(setter)]
+  pub fn set_image_smoothing_enabled(&mut self, value: bool) {
+    self.state.image_smoothing_enabled = value;
+  }
+
+  #[napi(getter, ts_return_type = "'low' | 'medium' | 'high'")]
+  pub fn image_smoothing_quality(&self) -> String {
+    match self.state.image_smoothing_quality {
+      ImageSmoothingQuality::Low => "low",
+      ImageSmoothingQuality::Medium => "medium",
+      ImageSmoothingQuality::High => "high",
+    }.to_string()
+  }
+
+  #[napi(setter, ts_args_type = "value: 'low' | 'medium' | 'high'")]
+  pub fn set_image_smoothing_quality(&mut self, value: String) {
+    // Invalid values are ignored
+    self.state.image_smoothing_quality = match value.as_str() {
+      "low" => ImageSmoothingQuality::Low,
+      "medium" => ImageSmoothingQuality::Medium,
+      "high" => ImageSmoothingQuality::High,
+      _ => return,
+    };
   }
 
   // Text methods
@@ -523,8 +826,8 @@ impl CanvasRenderingContext2D {
   #[napi]
   pub fn fill_text(&self, text: String, x: f64, y: f64, _max_width: Option<f64>) -> Result<()> {
     // This is a simplified implementation
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
 
     let font = Font::default();
     let text_blob = TextBlob::new(&text, &font).ok_or_else(|| {
@@ -533,95 +836,429 @@ impl CanvasRenderingContext2D {
 
     let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
     paint.set_style(skia_safe::PaintStyle::Fill);
+    self.apply_fill_style(&mut paint)?;
 
-    canvas.draw_text_blob(text_blob, (x as f32, y as f32), &paint);
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_text_blob(&text_blob, (x as f32, y as f32), paint);
+    });
 
     Ok(())
   }
 
   #[napi]
   pub fn stroke_text(&self, text: String, x: f64, y: f64, _max_width: Option<f64>) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
+    let mut surface = self.surface()?;
+    let canvas = surface.canvas();
 
     let font = Font::default();
     let text_blob = TextBlob::new(&text, &font).ok_or_else(|| {
       Error::new(Status::GenericFailure, "Failed to create text blob")
     })?;
 
-    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
-    paint.set_style(skia_safe::PaintStyle::Stroke);
+    let mut paint = self.stroke_paint();
+    self.apply_stroke_style(&mut paint)?;
 
-    let line_width = *self.line_width.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock line_width mutex")
-    })?;
+    self.draw_with_shadow(canvas, &paint, |canvas, paint| {
+      canvas.draw_text_blob(&text_blob, (x as f32, y as f32), paint);
+    });
 
-    paint.set_stroke_width(line_width as f32);
+    Ok(())
+  }
 
-    canvas.draw_text_blob(text_blob, (x as f32, y as f32), &paint);
+  // State methods
+
+  #[napi]
+  pub fn save(&mut self, env: Env) -> Result<()> {
+    self.state_stack.push(self.state.clone_with_env(env)?);
+    self.surface()?.canvas().save();
 
     Ok(())
   }
 
-  // Transform methods
-
+  // Does nothing when there is no saved state
   #[napi]
-  pub fn save(&self) -> Result<()> {
-    let mut transform_stack = self.transform_stack.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock transform_stack mutex")
-    })?;
+  pub fn restore(&mut self) -> Result<()> {
+    let Some(state) = self.state_stack.pop() else {
+      return Ok(());
+    };
 
-    // Use default() instead of new_identity()
-    let current_transform = transform_stack.last().cloned().unwrap_or_else(|| Matrix::default());
-    transform_stack.push(current_transform);
+    self.state = state;
+
+    // Restores the clip
+    self.surface()?.canvas().restore();
 
     Ok(())
   }
 
+  // Transform methods, which all ignore non-finite arguments
+
   #[napi]
-  pub fn restore(&self) -> Result<()> {
-    let mut transform_stack = self.transform_stack.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock transform_stack mutex")
-    })?;
+  pub fn translate(&mut self, x: f64, y: f64) {
+    if !x.is_finite() || !y.is_finite() {
+      return;
+    }
+
+    self.state.transform.pre_translate((x as f32, y as f32));
+  }
 
-    if transform_stack.len() > 1 {
-      transform_stack.pop();
+  #[napi]
+  pub fn rotate(&mut self, angle: f64) {
+    if !angle.is_finite() {
+      return;
     }
 
-    Ok(())
+    self.state.transform.pre_rotate(angle.to_degrees() as f32, None);
   }
 
   #[napi]
-  pub fn translate(&self, x: f64, y: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
-    canvas.translate((x as f32, y as f32));
-    Ok(())
+  pub fn scale(&mut self, x: f64, y: f64) {
+    if !x.is_finite() || !y.is_finite() {
+      return;
+    }
+
+    self.state.transform.pre_scale((x as f32, y as f32), None);
   }
 
   #[napi]
-  pub fn rotate(&self, angle: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
-    canvas.rotate(angle as f32, None);
+  pub fn transform(&mut self, a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) {
+    if ![a, b, c, d, e, f].iter().all(|v| v.is_finite()) {
+      return;
+    }
+
+    self.state.transform.pre_concat(&matrix_from_components([a, b, c, d, e, f]));
+  }
+
+  // Either setTransform(a, b, c, d, e, f) or setTransform(matrix?)
+  #[napi(ts_args_type = "a?: number | DOMMatrix2DInit, b?: number, c?: number, d?: number, e?: number, f?: number")]
+  pub fn set_transform(
+    &mut self,
+    a: Option<Either<f64, DOMMatrix2DInit>>,
+    b: Option<f64>,
+    c: Option<f64>,
+    d: Option<f64>,
+    e: Option<f64>,
+    f: Option<f64>,
+  ) -> Result<()> {
+    let components = match a {
+      None => {
+        self.state.transform = Matrix::default();
+        return Ok(());
+      }
+      Some(Either::A(a)) => match (b, c, d, e, f) {
+        (Some(b), Some(c), Some(d), Some(e), Some(f)) => [a, b, c, d, e, f],
+        _ => {
+          return Err(Error::new(
+            Status::InvalidArg,
+            "TypeError: setTransform() takes either a matrix or 6 numbers",
+          ));
+        }
+      },
+      Some(Either::B(matrix)) => matrix.to_components()?,
+    };
+
+    if components.iter().all(|v| v.is_finite()) {
+      self.state.transform = matrix_from_components(components);
+    }
+
     Ok(())
   }
 
   #[napi]
-  pub fn scale(&self, x: f64, y: f64) -> Result<()> {
-    let canvas_ref = unsafe { &*self.canvas_ptr };
-    let canvas = get_skia_canvas(canvas_ref)?;
-    canvas.scale((x as f32, y as f32));
-    Ok(())
+  pub fn reset_transform(&mut self) {
+    self.state.transform = Matrix::default();
   }
 
   #[napi]
-  pub fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Result<LinearGradient> {
-      Ok(LinearGradient::new(x0, y0, x1, y1))
+  pub fn get_transform(&self) -> DOMMatrix {
+    DOMMatrix::from_skia_matrix(&self.state.transform)
+  }
+
+  #[napi]
+  pub fn create_linear_gradient(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Result<CanvasGradient> {
+    if ![x0, y0, x1, y1].iter().all(|v| v.is_finite()) {
+      return Err(Error::new(Status::InvalidArg, "TypeError: The provided coordinates must be finite"));
+    }
+
+    Ok(CanvasGradient::new_linear(x0, y0, x1, y1))
   }
 
   #[napi]
-  pub fn create_radial_gradient(&self, x0: f64, y0: f64, r0: f64, x1: f64, y1: f64, r1: f64) -> Result<RadialGradient> {
-      Ok(RadialGradient::new(x0, y0, r0, x1, y1, r1))
+  pub fn create_radial_gradient(&self, x0: f64, y0: f64, r0: f64, x1: f64, y1: f64, r1: f64) -> Result<CanvasGradient> {
+    if ![x0, y0, r0, x1, y1, r1].iter().all(|v| v.is_finite()) {
+      return Err(Error::new(Status::InvalidArg, "TypeError: The provided coordinates must be finite"));
+    }
+
+    if r0 < 0.0 || r1 < 0.0 {
+      return Err(Error::new(Status::InvalidArg, "IndexSizeError: The radius provided is negative"));
+    }
+
+    Ok(CanvasGradient::new_radial(x0, y0, r0, x1, y1, r1))
+  }
+
+  #[napi]
+  pub fn create_conic_gradient(&self, start_angle: f64, x: f64, y: f64) -> Result<CanvasGradient> {
+    if ![start_angle, x, y].iter().all(|v| v.is_finite()) {
+      return Err(Error::new(Status::InvalidArg, "TypeError: The provided values must be finite"));
+    }
+
+    Ok(CanvasGradient::new_conic(start_angle, x, y))
+  }
+
+  // Returns null when the image has nothing to draw yet
+  #[napi(ts_args_type = "image: Canvas | Image | ImageData | ImageBitmap, repetition?: string | null")]
+  pub fn create_pattern(&self, image: ImageSource, repetition: Option<String>) -> Result<Option<CanvasPattern>> {
+    let Some(image) = source_image(&image)? else {
+      return Ok(None);
+    };
+
+    CanvasPattern::from_image(image, repetition).map(Some)
+  }
+
+  // Path helpers
+
+  // Maps a point given to a path method to device space
+  fn device_point(&self, x: f64, y: f64) -> Point {
+    self.state.transform.map_point((x as f32, y as f32))
+  }
+
+  // Appends a shape built in user space to the current path
+  fn add_to_current_path(&mut self, shape: &Path) {
+    self.current_path.add_path_matrix(shape, &self.state.transform, None);
+  }
+
+  // The given path, or the current path when omitted, in device space
+  fn device_path(&self, path: Option<&Path2D>) -> Path {
+    match path {
+      Some(path) => path.sk_path().with_transform(&self.state.transform),
+      None => self.current_path.clone(),
+    }
+  }
+
+  // The given path, or the current path when omitted, in user space for
+  // drawing with the transform applied. The current path can't be mapped
+  // back under a non-invertible transform, which draws nothing anyway.
+  fn user_path(&self, path: Option<&Path2D>) -> Option<Path> {
+    match path {
+      Some(path) => Some(path.sk_path().clone()),
+      None => self.state.transform.invert().map(|inverse| self.current_path.with_transform(&inverse)),
+    }
+  }
+
+  // Borrows the surface for the rest of the call, with the current transform
+  // applied to its canvas
+  fn surface(&self) -> Result<RefMut<'_, Surface>> {
+    let mut surface = borrow_surface(&self.surface)?;
+
+    let canvas = surface.canvas();
+    canvas.reset_matrix();
+    canvas.concat(&self.state.transform);
+
+    Ok(surface)
+  }
+
+  // Paint helpers
+
+  // A paint with the current line settings, before the stroke style
+  fn stroke_paint(&self) -> Paint {
+    let state = &self.state;
+
+    let mut paint = Paint::new(Color4f::new(0.0, 0.0, 0.0, 1.0), None);
+    paint.set_style(skia_safe::PaintStyle::Stroke);
+    paint.set_stroke_width(state.line_width as f32);
+
+    paint.set_stroke_cap(match state.line_cap {
+      LineCap::Butt => PaintCap::Butt,
+      LineCap::Round => PaintCap::Round,
+      LineCap::Square => PaintCap::Square,
+    });
+
+    paint.set_stroke_join(match state.line_join {
+      LineJoin::Miter => PaintJoin::Miter,
+      LineJoin::Round => PaintJoin::Round,
+      LineJoin::Bevel => PaintJoin::Bevel,
+    });
+
+    paint.set_stroke_miter(state.miter_limit as f32);
+
+    if !state.line_dash.is_empty() {
+      // Skia returns None when all segments are zero, which strokes a solid line
+      let intervals: Vec<f32> = state.line_dash.iter().map(|&v| v as f32).collect();
+      paint.set_path_effect(PathEffect::dash(&intervals, state.line_dash_offset as f32));
+    }
+
+    paint
+  }
+
+  fn apply_fill_style(&self, paint: &mut Paint) -> Result<()> {
+    self.state.fill_style.apply_to_paint(paint, self.sampling_options())?;
+    self.apply_compositing(paint);
+
+    Ok(())
+  }
+
+  fn apply_stroke_style(&self, paint: &mut Paint) -> Result<()> {
+    self.state.stroke_style.apply_to_paint(paint, self.sampling_options())?;
+    self.apply_compositing(paint);
+
+    Ok(())
+  }
+
+  // Applies globalAlpha and globalCompositeOperation, after the style has set
+  // the paint color. The paint alpha also modulates gradient and pattern shaders.
+  fn apply_compositing(&self, paint: &mut Paint) {
+    paint.set_alpha_f(paint.alpha_f() * self.state.global_alpha as f32);
+    paint.set_blend_mode(self.blend_mode());
+  }
+
+  // The Skia blend mode for globalCompositeOperation
+  fn blend_mode(&self) -> BlendMode {
+    match self.state.global_composite_operation {
+      CompositeOperation::SourceOver => BlendMode::SrcOver,
+      CompositeOperation::SourceIn => BlendMode::SrcIn,
+      CompositeOperation::SourceOut => BlendMode::SrcOut,
+      CompositeOperation::SourceAtop => BlendMode::SrcATop,
+      CompositeOperation::DestinationOver => BlendMode::DstOver,
+      CompositeOperation::DestinationIn => BlendMode::DstIn,
+      CompositeOperation::DestinationOut => BlendMode::DstOut,
+      CompositeOperation::DestinationAtop => BlendMode::DstATop,
+      CompositeOperation::Lighter => BlendMode::Plus,
+      CompositeOperation::Copy => BlendMode::Src,
+      CompositeOperation::Xor => BlendMode::Xor,
+      CompositeOperation::Multiply => BlendMode::Multiply,
+      CompositeOperation::Screen => BlendMode::Screen,
+      CompositeOperation::Overlay => BlendMode::Overlay,
+      CompositeOperation::Darken => BlendMode::Darken,
+      CompositeOperation::Lighten => BlendMode::Lighten,
+      CompositeOperation::ColorDodge => BlendMode::ColorDodge,
+      CompositeOperation::ColorBurn => BlendMode::ColorBurn,
+      CompositeOperation::HardLight => BlendMode::HardLight,
+      CompositeOperation::SoftLight => BlendMode::SoftLight,
+      CompositeOperation::Difference => BlendMode::Difference,
+      CompositeOperation::Exclusion => BlendMode::Exclusion,
+      CompositeOperation::Hue => BlendMode::Hue,
+      CompositeOperation::Saturation => BlendMode::Saturation,
+      CompositeOperation::Color => BlendMode::Color,
+      CompositeOperation::Luminosity => BlendMode::Luminosity,
+    }
+  }
+
+  // Draws with the paint, preceded by the shadow when one is visible. The
+  // shadow is rendered in a layer without the transform, so its offset and
+  // blur are in canvas pixels, and the layer is composited with the current
+  // operation like the shape itself.
+  fn draw_with_shadow(
+    &self,
+    canvas: &skia_safe::Canvas,
+    paint: &Paint,
+    draw: impl Fn(&skia_safe::Canvas, &Paint),
+  ) {
+    if let Some(shadow_filter) = self.shadow_filter() {
+      let mut layer_paint = Paint::default();
+      layer_paint.set_image_filter(shadow_filter);
+      layer_paint.set_blend_mode(self.blend_mode());
+
+      let mut shadow_paint = paint.clone();
+      shadow_paint.set_blend_mode(BlendMode::SrcOver);
+
+      canvas.save();
+      canvas.reset_matrix();
+      canvas.save_layer(&SaveLayerRec::default().paint(&layer_paint));
+      canvas.concat(&self.state.transform);
+      draw(canvas, &shadow_paint);
+      canvas.restore();
+      canvas.restore();
+    }
+
+    // Operations such as source-in also change the destination outside the
+    // shape, so the shape is drawn into a layer that is composited as a whole
+    if self.is_full_canvas_operation() {
+      let mut layer_paint = Paint::default();
+      layer_paint.set_blend_mode(self.blend_mode());
+
+      let mut shape_paint = paint.clone();
+      shape_paint.set_blend_mode(BlendMode::SrcOver);
+
+      canvas.save_layer(&SaveLayerRec::default().paint(&layer_paint));
+      draw(canvas, &shape_paint);
+      canvas.restore();
+    } else {
+      draw(canvas, paint);
+    }
+  }
+
+  // Whether the operation clears the destination where the source is
+  // transparent, which a plain draw would leave untouched
+  fn is_full_canvas_operation(&self) -> bool {
+    matches!(
+      self.state.global_composite_operation,
+      CompositeOperation::SourceIn
+        | CompositeOperation::SourceOut
+        | CompositeOperation::DestinationIn
+        | CompositeOperation::DestinationAtop
+        | CompositeOperation::Copy
+    )
+  }
+
+  // Returns None when shadows are not drawn, i.e. the color is transparent or
+  // there is neither blur nor offset
+  fn shadow_filter(&self) -> Option<ImageFilter> {
+    let state = &self.state;
+
+    if state.shadow_color.a == 0.0
+      || (state.shadow_blur == 0.0 && state.shadow_offset_x == 0.0 && state.shadow_offset_y == 0.0)
+    {
+      return None;
+    }
+
+    // The spec defines the blur as a Gaussian with half the shadowBlur as sigma
+    let sigma = (state.shadow_blur / 2.0) as f32;
+
+    image_filters::drop_shadow_only(
+      (state.shadow_offset_x as f32, state.shadow_offset_y as f32),
+      (sigma, sigma),
+      state.shadow_color,
+      None,
+      None,
+      image_filters::CropRect::NO_CROP_RECT,
+    )
+  }
+
+  // Sampling used for patterns, from imageSmoothingEnabled and imageSmoothingQuality
+  fn sampling_options(&self) -> SamplingOptions {
+    if !self.state.image_smoothing_enabled {
+      return SamplingOptions::new(FilterMode::Nearest, MipmapMode::None);
+    }
+
+    match self.state.image_smoothing_quality {
+      ImageSmoothingQuality::Low => SamplingOptions::new(FilterMode::Linear, MipmapMode::None),
+      ImageSmoothingQuality::Medium => SamplingOptions::new(FilterMode::Linear, MipmapMode::Nearest),
+      ImageSmoothingQuality::High => SamplingOptions::from(CubicResampler::mitchell()),
+    }
+  }
+}
+
+// Resolves the (path?, fillRule?) overloads of fill and clip
+fn split_fill_rule_args(
+  path_or_fill_rule: Option<Either<&Path2D, String>>,
+  fill_rule: Option<String>,
+) -> Result<(Option<&Path2D>, PathFillType)> {
+  let (path, fill_rule) = match path_or_fill_rule {
+    Some(Either::A(path)) => (Some(path), fill_rule),
+    Some(Either::B(fill_rule)) => (None, Some(fill_rule)),
+    None => (None, fill_rule),
+  };
+
+  Ok((path, parse_fill_rule(fill_rule.as_deref())?))
+}
+
+// Parses a fill rule, which defaults to "nonzero"
+fn parse_fill_rule(fill_rule: Option<&str>) -> Result<PathFillType> {
+  match fill_rule {
+    Some("nonzero") | None => Ok(PathFillType::Winding),
+    Some("evenodd") => Ok(PathFillType::EvenOdd),
+    Some(other) => Err(Error::new(
+      Status::InvalidArg,
+      format!("TypeError: '{}' is not a valid fill rule", other),
+    )),
   }
 }
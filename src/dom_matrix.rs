@@ -0,0 +1,1113 @@
+use napi::bindgen_prelude::*;
+use napi::JsObject;
+use napi_derive::napi;
+use skia_safe::Matrix;
+
+// A plain object describing a 2D matrix, either through a..f or the
+// equivalent m11, m12, m21, m22, m41 and m42 members
+#[napi(object)]
+pub struct DOMMatrix2DInit {
+  pub a: Option<f64>,
+  pub b: Option<f64>,
+  pub c: Option<f64>,
+  pub d: Option<f64>,
+  pub e: Option<f64>,
+  pub f: Option<f64>,
+  pub m11: Option<f64>,
+  pub m12: Option<f64>,
+  pub m21: Option<f64>,
+  pub m22: Option<f64>,
+  pub m41: Option<f64>,
+  pub m42: Option<f64>,
+}
+
+impl DOMMatrix2DInit {
+  // Validates and fixes up the dictionary per the Geometry Interfaces spec,
+  // returning [m11, m12, m21, m22, m41, m42]
+  pub(crate) fn to_components(&self) -> Result<[f64; 6]> {
+    fixup_2d(
+      [self.a, self.b, self.c, self.d, self.e, self.f],
+      [self.m11, self.m12, self.m21, self.m22, self.m41, self.m42],
+    )
+  }
+
+  // Returns None when any component is not finite, which callers ignore
+  pub(crate) fn to_matrix(&self) -> Result<Option<Matrix>> {
+    let components = self.to_components()?;
+
+    if !components.iter().all(|v| v.is_finite()) {
+      return Ok(None);
+    }
+
+    Ok(Some(matrix_from_components(components)))
+  }
+}
+
+// Like DOMMatrix2DInit, with the members only 3D matrices use
+#[napi(object)]
+pub struct DOMMatrixInit {
+  pub a: Option<f64>,
+  pub b: Option<f64>,
+  pub c: Option<f64>,
+  pub d: Option<f64>,
+  pub e: Option<f64>,
+  pub f: Option<f64>,
+  pub m11: Option<f64>,
+  pub m12: Option<f64>,
+  pub m13: Option<f64>,
+  pub m14: Option<f64>,
+  pub m21: Option<f64>,
+  pub m22: Option<f64>,
+  pub m23: Option<f64>,
+  pub m24: Option<f64>,
+  pub m31: Option<f64>,
+  pub m32: Option<f64>,
+  pub m33: Option<f64>,
+  pub m34: Option<f64>,
+  pub m41: Option<f64>,
+  pub m42: Option<f64>,
+  pub m43: Option<f64>,
+  pub m44: Option<f64>,
+  #[napi(js_name = "is2D")]
+  pub is_2d: Option<bool>,
+}
+
+#[napi(object)]
+pub struct DOMPointInit {
+  pub x: Option<f64>,
+  pub y: Option<f64>,
+  pub z: Option<f64>,
+  pub w: Option<f64>,
+}
+
+// Canvas matrices map (x, y) to (a * x + c * y + e, b * x + d * y + f)
+pub(crate) fn matrix_from_components([a, b, c, d, e, f]: [f64; 6]) -> Matrix {
+  Matrix::new_all(
+    a as f32, c as f32, e as f32,
+    b as f32, d as f32, f as f32,
+    0.0, 0.0, 1.0,
+  )
+}
+
+fn fixup_2d(short: [Option<f64>; 6], long: [Option<f64>; 6]) -> Result<[f64; 6]> {
+  const NAMES: [(&str, &str); 6] = [("a", "m11"), ("b", "m12"), ("c", "m21"), ("d", "m22"), ("e", "m41"), ("f", "m42")];
+  const DEFAULTS: [f64; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+  let mut components = DEFAULTS;
+
+  for (i, component) in components.iter_mut().enumerate() {
+    // Both members may be given as long as they are the same (SameValueZero)
+    if let (Some(x), Some(y)) = (short[i], long[i]) {
+      if x != y && !(x.is_nan() && y.is_nan()) {
+        return Err(Error::new(
+          Status::InvalidArg,
+          format!("TypeError: The '{}' and '{}' members must be equal if both are given", NAMES[i].0, NAMES[i].1),
+        ));
+      }
+    }
+
+    *component = long[i].or(short[i]).unwrap_or(DEFAULTS[i]);
+  }
+
+  Ok(components)
+}
+
+const IDENTITY: [f64; 16] = [
+  1.0, 0.0, 0.0, 0.0,
+  0.0, 1.0, 0.0, 0.0,
+  0.0, 0.0, 1.0, 0.0,
+  0.0, 0.0, 0.0, 1.0,
+];
+
+// The members that must keep their identity value in a 2D matrix, as
+// (index, identity value)
+const MEMBERS_3D: [(usize, f64); 10] = [
+  (2, 0.0), (3, 0.0), (6, 0.0), (7, 0.0), (8, 0.0),
+  (9, 0.0), (10, 1.0), (11, 0.0), (14, 0.0), (15, 1.0),
+];
+
+// The 4x4 matrix shared by DOMMatrix and DOMMatrixReadOnly. Elements are
+// stored column by column, in the order m11, m12, m13, m14, m21, ...
+#[derive(Clone, Copy)]
+struct Matrix4 {
+  m: [f64; 16],
+  is_2d: bool,
+}
+
+impl Matrix4 {
+  fn identity() -> Self {
+    Self { m: IDENTITY, is_2d: true }
+  }
+
+  fn from_2d([a, b, c, d, e, f]: [f64; 6]) -> Self {
+    Self {
+      m: [
+        a, b, 0.0, 0.0,
+        c, d, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        e, f, 0.0, 1.0,
+      ],
+      is_2d: true,
+    }
+  }
+
+  fn from_3d(m: [f64; 16]) -> Self {
+    Self { m, is_2d: false }
+  }
+
+  fn from_init(init: &DOMMatrixInit) -> Result<Self> {
+    let [a, b, c, d, e, f] = fixup_2d(
+      [init.a, init.b, init.c, init.d, init.e, init.f],
+      [init.m11, init.m12, init.m21, init.m22, init.m41, init.m42],
+    )?;
+
+    let m = [
+      a, b, init.m13.unwrap_or(0.0), init.m14.unwrap_or(0.0),
+      c, d, init.m23.unwrap_or(0.0), init.m24.unwrap_or(0.0),
+      init.m31.unwrap_or(0.0), init.m32.unwrap_or(0.0), init.m33.unwrap_or(1.0), init.m34.unwrap_or(0.0),
+      e, f, init.m43.unwrap_or(0.0), init.m44.unwrap_or(1.0),
+    ];
+
+    let is_flat = MEMBERS_3D.iter().all(|&(i, value)| m[i] == value);
+
+    match init.is_2d {
+      Some(true) if !is_flat => Err(Error::new(
+        Status::InvalidArg,
+        "TypeError: A 2D matrix can't have 3D members other than their defaults",
+      )),
+      Some(is_2d) => Ok(Self { m, is_2d }),
+      None => Ok(Self { m, is_2d: is_flat }),
+    }
+  }
+
+  fn from_sequence(values: &[f64]) -> Result<Self> {
+    match values.len() {
+      6 => Ok(Self::from_2d(std::array::from_fn(|i| values[i]))),
+      16 => Ok(Self::from_3d(std::array::from_fn(|i| values[i]))),
+      len => Err(Error::new(
+        Status::InvalidArg,
+        format!("TypeError: Expected a sequence of 6 or 16 numbers, got {}", len),
+      )),
+    }
+  }
+
+  fn from_constructor_init(init: Option<Either<String, Vec<f64>>>) -> Result<Self> {
+    match init {
+      None => Ok(Self::identity()),
+      Some(Either::A(transform_list)) => Self::parse(&transform_list),
+      Some(Either::B(values)) => Self::from_sequence(&values),
+    }
+  }
+
+  // Parses a CSS transform list such as "matrix(1, 0, 0, 1, 10, 20)" or
+  // "translate(10px, 20px) rotate(45deg)"
+  fn parse(transform_list: &str) -> Result<Self> {
+    parse_transform_list(transform_list).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("SyntaxError: Failed to parse '{}' as a transform list", transform_list),
+      )
+    })
+  }
+
+  fn is_finite(&self) -> bool {
+    self.m.iter().all(|v| v.is_finite())
+  }
+
+  fn is_identity(&self) -> bool {
+    self.m == IDENTITY
+  }
+
+  // Returns self × other
+  fn multiply(&self, other: &Self) -> Self {
+    let (a, b) = (&self.m, &other.m);
+
+    Self {
+      m: std::array::from_fn(|i| {
+        let (column, row) = (i / 4, i % 4);
+        (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum()
+      }),
+      is_2d: self.is_2d && other.is_2d,
+    }
+  }
+
+  fn post_multiply(&mut self, other: &Self) {
+    *self = self.multiply(other);
+  }
+
+  fn translate(&mut self, tx: f64, ty: f64, tz: f64) {
+    let mut translation = Self::identity();
+    translation.m[12] = tx;
+    translation.m[13] = ty;
+    translation.m[14] = tz;
+    translation.is_2d = tz == 0.0;
+
+    self.post_multiply(&translation);
+  }
+
+  fn scale(&mut self, sx: f64, sy: f64, sz: f64, origin: [f64; 3]) {
+    let mut scale = Self::identity();
+    scale.m[0] = sx;
+    scale.m[5] = sy;
+    scale.m[10] = sz;
+
+    // Translating to the origin and back cancels out for x and y, but a z
+    // origin leaves a z translation, as does a z scale
+    let is_2d = self.is_2d && sz == 1.0 && origin[2] == 0.0;
+
+    self.translate(origin[0], origin[1], origin[2]);
+    self.post_multiply(&scale);
+    self.translate(-origin[0], -origin[1], -origin[2]);
+
+    self.is_2d = is_2d;
+  }
+
+  // The angle is in degrees, around the (x, y, z) axis
+  fn rotate_axis_angle(&mut self, x: f64, y: f64, z: f64, angle: f64) {
+    let length = (x * x + y * y + z * z).sqrt();
+    let is_2d = x == 0.0 && y == 0.0;
+
+    if length == 0.0 {
+      self.is_2d &= is_2d;
+      return;
+    }
+
+    let (x, y, z) = (x / length, y / length, z / length);
+    let half = angle.to_radians() / 2.0;
+    let sc = half.sin() * half.cos();
+    let sq = half.sin() * half.sin();
+
+    let rotation = Self {
+      m: [
+        1.0 - 2.0 * (y * y + z * z) * sq,
+        2.0 * (x * y * sq + z * sc),
+        2.0 * (x * z * sq - y * sc),
+        0.0,
+        2.0 * (x * y * sq - z * sc),
+        1.0 - 2.0 * (x * x + z * z) * sq,
+        2.0 * (y * z * sq + x * sc),
+        0.0,
+        2.0 * (x * z * sq + y * sc),
+        2.0 * (y * z * sq - x * sc),
+        1.0 - 2.0 * (x * x + y * y) * sq,
+        0.0,
+        0.0, 0.0, 0.0, 1.0,
+      ],
+      is_2d,
+    };
+
+    self.post_multiply(&rotation);
+  }
+
+  // A single angle rotates around the z axis, like 2D rotations
+  fn rotate(&mut self, rot_x: Option<f64>, rot_y: Option<f64>, rot_z: Option<f64>) {
+    let (rot_x, rot_y, rot_z) = match (rot_x, rot_y, rot_z) {
+      (rot_x, None, None) => (0.0, 0.0, rot_x.unwrap_or(0.0)),
+      (rot_x, rot_y, rot_z) => (rot_x.unwrap_or(0.0), rot_y.unwrap_or(0.0), rot_z.unwrap_or(0.0)),
+    };
+
+    self.rotate_axis_angle(0.0, 0.0, 1.0, rot_z);
+
+    if rot_y != 0.0 {
+      self.rotate_axis_angle(0.0, 1.0, 0.0, rot_y);
+    }
+
+    if rot_x != 0.0 {
+      self.rotate_axis_angle(1.0, 0.0, 0.0, rot_x);
+    }
+  }
+
+  fn rotate_from_vector(&mut self, x: f64, y: f64) {
+    let angle = if x == 0.0 && y == 0.0 { 0.0 } else { y.atan2(x).to_degrees() };
+    self.rotate_axis_angle(0.0, 0.0, 1.0, angle);
+  }
+
+  fn skew_x(&mut self, angle: f64) {
+    let mut skew = Self::identity();
+    skew.m[4] = angle.to_radians().tan();
+    self.post_multiply(&skew);
+  }
+
+  fn skew_y(&mut self, angle: f64) {
+    let mut skew = Self::identity();
+    skew.m[1] = angle.to_radians().tan();
+    self.post_multiply(&skew);
+  }
+
+  // A matrix that can't be inverted becomes all NaN
+  fn invert(&mut self) {
+    let inverse = if self.is_2d {
+      let [a, b, _, _, c, d, _, _, _, _, _, _, e, f, _, _] = self.m;
+      let det = a * d - b * c;
+
+      (det != 0.0 && det.is_finite()).then(|| Self::from_2d([
+        d / det,
+        -b / det,
+        -c / det,
+        a / det,
+        (c * f - d * e) / det,
+        (b * e - a * f) / det,
+      ]))
+    } else {
+      invert_4x4(&self.m).map(Self::from_3d)
+    };
+
+    *self = inverse.unwrap_or(Self::from_3d([f64::NAN; 16]));
+  }
+
+  fn transform_point(&self, point: [f64; 4]) -> [f64; 4] {
+    std::array::from_fn(|row| (0..4).map(|column| self.m[column * 4 + row] * point[column]).sum())
+  }
+
+  fn to_css_string(self) -> Result<String> {
+    if !self.is_finite() {
+      return Err(Error::new(
+        Status::GenericFailure,
+        "InvalidStateError: Matrices with non-finite values can't be serialized",
+      ));
+    }
+
+    let values: Vec<String> = if self.is_2d {
+      [0, 1, 4, 5, 12, 13].iter().map(|&i| format_number(self.m[i])).collect()
+    } else {
+      self.m.iter().map(|&v| format_number(v)).collect()
+    };
+
+    let name = if self.is_2d { "matrix" } else { "matrix3d" };
+    Ok(format!("{}({})", name, values.join(", ")))
+  }
+
+  fn to_init(self) -> DOMMatrixInit {
+    let m = self.m.map(Some);
+
+    DOMMatrixInit {
+      a: m[0], b: m[1], c: m[4], d: m[5], e: m[12], f: m[13],
+      m11: m[0], m12: m[1], m13: m[2], m14: m[3],
+      m21: m[4], m22: m[5], m23: m[6], m24: m[7],
+      m31: m[8], m32: m[9], m33: m[10], m34: m[11],
+      m41: m[12], m42: m[13], m43: m[14], m44: m[15],
+      is_2d: Some(self.is_2d),
+    }
+  }
+
+  fn set(&mut self, index: usize, value: f64) {
+    self.m[index] = value;
+
+    // Only the 3D members can make a 2D matrix 3D
+    if let Some(&(_, identity)) = MEMBERS_3D.iter().find(|&&(i, _)| i == index) {
+      if value != identity {
+        self.is_2d = false;
+      }
+    }
+  }
+}
+
+fn invert_4x4(m: &[f64; 16]) -> Option<[f64; 16]> {
+  // Gauss-Jordan elimination on [M | I], one row per matrix row
+  let mut rows: [[f64; 8]; 4] = std::array::from_fn(|row| {
+    std::array::from_fn(|column| match column {
+      0..=3 => m[column * 4 + row],
+      _ if column - 4 == row => 1.0,
+      _ => 0.0,
+    })
+  });
+
+  for column in 0..4 {
+    let pivot = (column..4).max_by(|&i, &j| rows[i][column].abs().total_cmp(&rows[j][column].abs()))?;
+    let pivot_value = rows[pivot][column];
+
+    if pivot_value == 0.0 || !pivot_value.is_finite() {
+      return None;
+    }
+
+    rows.swap(column, pivot);
+    let pivot_row = rows[column].map(|v| v / pivot_value);
+
+    for (i, row) in rows.iter_mut().enumerate() {
+      if i == column {
+        *row = pivot_row;
+        continue;
+      }
+
+      let factor = row[column];
+      for (value, pivot_value) in row.iter_mut().zip(pivot_row) {
+        *value -= factor * pivot_value;
+      }
+    }
+  }
+
+  Some(std::array::from_fn(|i| rows[i % 4][4 + i / 4]))
+}
+
+fn parse_transform_list(input: &str) -> Option<Matrix4> {
+  let input = input.trim();
+  let mut matrix = Matrix4::identity();
+
+  if input.is_empty() || input.eq_ignore_ascii_case("none") {
+    return Some(matrix);
+  }
+
+  let mut rest = input;
+
+  while !rest.is_empty() {
+    let open = rest.find('(')?;
+    let close = rest.find(')')?;
+    if close < open {
+      return None;
+    }
+
+    let name = rest[..open].trim().to_ascii_lowercase();
+    let args = rest[open + 1..close]
+      .split(',')
+      .map(str::trim)
+      .collect::<Vec<_>>();
+
+    matrix.post_multiply(&parse_transform_function(&name, &args)?);
+    rest = rest[close + 1..].trim_start();
+  }
+
+  Some(matrix)
+}
+
+fn parse_transform_function(name: &str, args: &[&str]) -> Option<Matrix4> {
+  let numbers = || args.iter().map(|arg| parse_number(arg)).collect::<Option<Vec<_>>>();
+  let lengths = || args.iter().map(|arg| parse_length(arg)).collect::<Option<Vec<_>>>();
+  let angles = || args.iter().map(|arg| parse_angle(arg)).collect::<Option<Vec<_>>>();
+
+  let mut matrix = Matrix4::identity();
+
+  match (name, args.len()) {
+    ("matrix", 6) | ("matrix3d", 16) => {
+      matrix = Matrix4::from_sequence(&numbers()?).ok()?;
+    },
+    ("translate", 1 | 2) => {
+      let values = lengths()?;
+      matrix.translate(values[0], values.get(1).copied().unwrap_or(0.0), 0.0);
+    },
+    ("translatex", 1) => matrix.translate(parse_length(args[0])?, 0.0, 0.0),
+    ("translatey", 1) => matrix.translate(0.0, parse_length(args[0])?, 0.0),
+    ("translatez", 1) => matrix.translate(0.0, 0.0, parse_length(args[0])?),
+    ("translate3d", 3) => {
+      let values = lengths()?;
+      matrix.translate(values[0], values[1], values[2]);
+    },
+    ("scale", 1 | 2) => {
+      let values = numbers()?;
+      matrix.scale(values[0], values.get(1).copied().unwrap_or(values[0]), 1.0, [0.0; 3]);
+    },
+    ("scalex", 1) => matrix.scale(parse_number(args[0])?, 1.0, 1.0, [0.0; 3]),
+    ("scaley", 1) => matrix.scale(1.0, parse_number(args[0])?, 1.0, [0.0; 3]),
+    ("scalez", 1) => matrix.scale(1.0, 1.0, parse_number(args[0])?, [0.0; 3]),
+    ("scale3d", 3) => {
+      let values = numbers()?;
+      matrix.scale(values[0], values[1], values[2], [0.0; 3]);
+    },
+    ("rotate", 1) => matrix.rotate_axis_angle(0.0, 0.0, 1.0, parse_angle(args[0])?),
+    ("rotatex", 1) => matrix.rotate_axis_angle(1.0, 0.0, 0.0, parse_angle(args[0])?),
+    ("rotatey", 1) => matrix.rotate_axis_angle(0.0, 1.0, 0.0, parse_angle(args[0])?),
+    ("rotatez", 1) => matrix.rotate_axis_angle(0.0, 0.0, 1.0, parse_angle(args[0])?),
+    ("rotate3d", 4) => {
+      let axis = args[..3].iter().map(|arg| parse_number(arg)).collect::<Option<Vec<_>>>()?;
+      matrix.rotate_axis_angle(axis[0], axis[1], axis[2], parse_angle(args[3])?);
+    },
+    ("skew", 1 | 2) => {
+      let values = angles()?;
+      matrix.m[4] = values[0].to_radians().tan();
+      matrix.m[1] = values.get(1).copied().unwrap_or(0.0).to_radians().tan();
+    },
+    ("skewx", 1) => matrix.skew_x(parse_angle(args[0])?),
+    ("skewy", 1) => matrix.skew_y(parse_angle(args[0])?),
+    ("perspective", 1) => {
+      let distance = parse_length(args[0])?;
+      if distance != 0.0 {
+        matrix.m[11] = -1.0 / distance;
+      }
+    },
+    _ => return None,
+  }
+
+  // 3D transform functions make the matrix 3D even with flat values
+  if name.ends_with('z') || name.ends_with("3d") || name == "rotatex" || name == "rotatey" || name == "perspective" {
+    matrix.is_2d = false;
+  }
+
+  Some(matrix)
+}
+
+// Splits "10px" into (10, "px")
+fn split_unit(arg: &str) -> Option<(f64, &str)> {
+  let number_end = arg.trim_end_matches(|c: char| c.is_ascii_alphabetic()).len();
+  let value = arg[..number_end].parse::<f64>().ok()?;
+
+  value.is_finite().then_some((value, &arg[number_end..]))
+}
+
+fn parse_number(arg: &str) -> Option<f64> {
+  match split_unit(arg)? {
+    (value, "") => Some(value),
+    _ => None,
+  }
+}
+
+// Only absolute lengths are meaningful outside of a document
+fn parse_length(arg: &str) -> Option<f64> {
+  let (value, unit) = split_unit(arg)?;
+
+  match unit.to_ascii_lowercase().as_str() {
+    "px" => Some(value),
+    "" if value == 0.0 => Some(value),
+    "in" => Some(value * 96.0),
+    "cm" => Some(value * 96.0 / 2.54),
+    "mm" => Some(value * 96.0 / 25.4),
+    "pt" => Some(value * 96.0 / 72.0),
+    "pc" => Some(value * 16.0),
+    _ => None,
+  }
+}
+
+// Returns degrees
+fn parse_angle(arg: &str) -> Option<f64> {
+  let (value, unit) = split_unit(arg)?;
+
+  match unit.to_ascii_lowercase().as_str() {
+    "deg" => Some(value),
+    "" if value == 0.0 => Some(value),
+    "rad" => Some(value.to_degrees()),
+    "grad" => Some(value * 0.9),
+    "turn" => Some(value * 360.0),
+    _ => None,
+  }
+}
+
+fn parse_float_array(values: impl Iterator<Item = f64>) -> Result<Matrix4> {
+  Matrix4::from_sequence(&values.collect::<Vec<_>>())
+}
+
+// Formats a finite number the way JS Number.prototype.toString does, which
+// switches to exponents below 1e-6 and from 1e21 on, and prints -0 as 0
+fn format_number(value: f64) -> String {
+  if value == 0.0 {
+    return "0".to_string();
+  }
+
+  // Rust's exponent format has the shortest digits that round-trip
+  let formatted = format!("{:e}", value.abs());
+  let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let k = digits.len() as i32;
+  let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+  let magnitude = if k <= n && n <= 21 {
+    format!("{}{}", digits, "0".repeat((n - k) as usize))
+  } else if 0 < n && n <= 21 {
+    format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+  } else if -6 < n && n <= 0 {
+    format!("0.{}{}", "0".repeat(-n as usize), digits)
+  } else {
+    let (first, rest) = digits.split_at(1);
+    let point = if rest.is_empty() { "" } else { "." };
+    let sign = if n > 0 { "+" } else { "-" };
+    format!("{}{}{}e{}{}", first, point, rest, sign, (n - 1).abs())
+  };
+
+  if value < 0.0 {
+    format!("-{}", magnitude)
+  } else {
+    magnitude
+  }
+}
+
+// Generates the a..f and m11..m44 accessors. A getter and its setter have to
+// be in the same impl block, so DOMMatrix gets its own.
+macro_rules! dom_matrix_members {
+  ($name:ident, $($getter:ident $(/ $setter:ident)? = $index:literal),* $(,)?) => {
+    #[napi]
+    impl $name {
+      $(
+        #[napi(getter)]
+        pub fn $getter(&self) -> f64 {
+          self.inner.m[$index]
+        }
+
+        $(
+          #[napi(setter)]
+          pub fn $setter(&mut self, value: f64) {
+            self.inner.set($index, value);
+          }
+        )?
+      )*
+    }
+  };
+}
+
+// The members and methods DOMMatrix shares with DOMMatrixReadOnly. Methods
+// that return a new matrix return a DOMMatrix in both.
+macro_rules! dom_matrix_read_only {
+  ($name:ident) => {
+    #[napi]
+    impl $name {
+      #[napi(constructor, ts_args_type = "init?: string | number[]")]
+      pub fn new(init: Option<Either<String, Vec<f64>>>) -> Result<Self> {
+        Ok(Self { inner: Matrix4::from_constructor_init(init)? })
+      }
+
+      #[napi(factory, ts_args_type = "other?: DOMMatrixInit")]
+      pub fn from_matrix(other: Option<DOMMatrixInit>) -> Result<Self> {
+        let inner = match other {
+          Some(other) => Matrix4::from_init(&other)?,
+          None => Matrix4::identity(),
+        };
+
+        Ok(Self { inner })
+      }
+
+      #[napi(factory)]
+      pub fn from_float32_array(array: Float32Array) -> Result<Self> {
+        Ok(Self { inner: parse_float_array(array.iter().map(|&v| v as f64))? })
+      }
+
+      #[napi(factory)]
+      pub fn from_float64_array(array: Float64Array) -> Result<Self> {
+        Ok(Self { inner: parse_float_array(array.iter().copied())? })
+      }
+
+      #[napi(getter, js_name = "is2D")]
+      pub fn is_2d(&self) -> bool {
+        self.inner.is_2d
+      }
+
+      #[napi(getter)]
+      pub fn is_identity(&self) -> bool {
+        self.inner.is_identity()
+      }
+
+      #[napi]
+      pub fn translate(&self, tx: Option<f64>, ty: Option<f64>, tz: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.translate(tx.unwrap_or(0.0), ty.unwrap_or(0.0), tz.unwrap_or(0.0));
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn scale(
+        &self,
+        scale_x: Option<f64>,
+        scale_y: Option<f64>,
+        scale_z: Option<f64>,
+        origin_x: Option<f64>,
+        origin_y: Option<f64>,
+        origin_z: Option<f64>,
+      ) -> DOMMatrix {
+        let mut inner = self.inner;
+        let scale_x = scale_x.unwrap_or(1.0);
+        inner.scale(
+          scale_x,
+          scale_y.unwrap_or(scale_x),
+          scale_z.unwrap_or(1.0),
+          [origin_x.unwrap_or(0.0), origin_y.unwrap_or(0.0), origin_z.unwrap_or(0.0)],
+        );
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn scale3d(&self, scale: Option<f64>, origin_x: Option<f64>, origin_y: Option<f64>, origin_z: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        let scale = scale.unwrap_or(1.0);
+        inner.scale(
+          scale,
+          scale,
+          scale,
+          [origin_x.unwrap_or(0.0), origin_y.unwrap_or(0.0), origin_z.unwrap_or(0.0)],
+        );
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn rotate(&self, rot_x: Option<f64>, rot_y: Option<f64>, rot_z: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.rotate(rot_x, rot_y, rot_z);
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn rotate_from_vector(&self, x: Option<f64>, y: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.rotate_from_vector(x.unwrap_or(0.0), y.unwrap_or(0.0));
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn rotate_axis_angle(&self, x: Option<f64>, y: Option<f64>, z: Option<f64>, angle: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.rotate_axis_angle(x.unwrap_or(0.0), y.unwrap_or(0.0), z.unwrap_or(0.0), angle.unwrap_or(0.0));
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn skew_x(&self, sx: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.skew_x(sx.unwrap_or(0.0));
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn skew_y(&self, sy: Option<f64>) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.skew_y(sy.unwrap_or(0.0));
+        DOMMatrix { inner }
+      }
+
+      #[napi(ts_args_type = "other?: DOMMatrixInit")]
+      pub fn multiply(&self, other: Option<DOMMatrixInit>) -> Result<DOMMatrix> {
+        let other = match other {
+          Some(other) => Matrix4::from_init(&other)?,
+          None => Matrix4::identity(),
+        };
+
+        Ok(DOMMatrix { inner: self.inner.multiply(&other) })
+      }
+
+      #[napi]
+      pub fn flip_x(&self) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.scale(-1.0, 1.0, 1.0, [0.0; 3]);
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn flip_y(&self) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.scale(1.0, -1.0, 1.0, [0.0; 3]);
+        DOMMatrix { inner }
+      }
+
+      #[napi]
+      pub fn inverse(&self) -> DOMMatrix {
+        let mut inner = self.inner;
+        inner.invert();
+        DOMMatrix { inner }
+      }
+
+      #[napi(ts_args_type = "point?: DOMPointInit")]
+      pub fn transform_point(&self, point: Option<DOMPointInit>) -> DOMPoint {
+        DOMPoint::from_init(point).transform(&self.inner)
+      }
+
+      #[napi]
+      pub fn to_float32_array(&self) -> Float32Array {
+        Float32Array::new(self.inner.m.iter().map(|&v| v as f32).collect())
+      }
+
+      #[napi]
+      pub fn to_float64_array(&self) -> Float64Array {
+        Float64Array::new(self.inner.m.to_vec())
+      }
+
+      #[napi(js_name = "toString")]
+      pub fn to_css_string(&self) -> Result<String> {
+        self.inner.to_css_string()
+      }
+
+      #[napi(js_name = "toJSON")]
+      pub fn to_json(&self) -> DOMMatrixInit {
+        self.inner.to_init()
+      }
+    }
+  };
+}
+
+#[napi]
+pub struct DOMMatrixReadOnly {
+  inner: Matrix4,
+}
+
+dom_matrix_read_only!(DOMMatrixReadOnly);
+
+dom_matrix_members!(
+  DOMMatrixReadOnly,
+  a = 0,
+  b = 1,
+  c = 4,
+  d = 5,
+  e = 12,
+  f = 13,
+  m11 = 0,
+  m12 = 1,
+  m13 = 2,
+  m14 = 3,
+  m21 = 4,
+  m22 = 5,
+  m23 = 6,
+  m24 = 7,
+  m31 = 8,
+  m32 = 9,
+  m33 = 10,
+  m34 = 11,
+  m41 = 12,
+  m42 = 13,
+  m43 = 14,
+  m44 = 15,
+);
+
+#[napi]
+pub struct DOMMatrix {
+  inner: Matrix4,
+}
+
+dom_matrix_read_only!(DOMMatrix);
+
+dom_matrix_members!(
+  DOMMatrix,
+  a / set_a = 0,
+  b / set_b = 1,
+  c / set_c = 4,
+  d / set_d = 5,
+  e / set_e = 12,
+  f / set_f = 13,
+  m11 / set_m11 = 0,
+  m12 / set_m12 = 1,
+  m13 / set_m13 = 2,
+  m14 / set_m14 = 3,
+  m21 / set_m21 = 4,
+  m22 / set_m22 = 5,
+  m23 / set_m23 = 6,
+  m24 / set_m24 = 7,
+  m31 / set_m31 = 8,
+  m32 / set_m32 = 9,
+  m33 / set_m33 = 10,
+  m34 / set_m34 = 11,
+  m41 / set_m41 = 12,
+  m42 / set_m42 = 13,
+  m43 / set_m43 = 14,
+  m44 / set_m44 = 15,
+);
+
+#[napi]
+impl DOMMatrix {
+  pub(crate) fn from_skia_matrix(matrix: &Matrix) -> Self {
+    let inner = Matrix4::from_2d([
+      matrix.scale_x() as f64,
+      matrix.skew_y() as f64,
+      matrix.skew_x() as f64,
+      matrix.scale_y() as f64,
+      matrix.translate_x() as f64,
+      matrix.translate_y() as f64,
+    ]);
+
+    Self { inner }
+  }
+
+  // The *Self methods modify the matrix in place and return it
+
+  #[napi(ts_args_type = "other?: DOMMatrixInit", ts_return_type = "this")]
+  pub fn multiply_self(&mut self, this: This<JsObject>, other: Option<DOMMatrixInit>) -> Result<JsObject> {
+    if let Some(other) = other {
+      self.inner.post_multiply(&Matrix4::from_init(&other)?);
+    }
+
+    Ok(this)
+  }
+
+  #[napi(ts_args_type = "other?: DOMMatrixInit", ts_return_type = "this")]
+  pub fn pre_multiply_self(&mut self, this: This<JsObject>, other: Option<DOMMatrixInit>) -> Result<JsObject> {
+    if let Some(other) = other {
+      self.inner = Matrix4::from_init(&other)?.multiply(&self.inner);
+    }
+
+    Ok(this)
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn translate_self(&mut self, this: This<JsObject>, tx: Option<f64>, ty: Option<f64>, tz: Option<f64>) -> JsObject {
+    self.inner.translate(tx.unwrap_or(0.0), ty.unwrap_or(0.0), tz.unwrap_or(0.0));
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn scale_self(
+    &mut self,
+    this: This<JsObject>,
+    scale_x: Option<f64>,
+    scale_y: Option<f64>,
+    scale_z: Option<f64>,
+    origin_x: Option<f64>,
+    origin_y: Option<f64>,
+    origin_z: Option<f64>,
+  ) -> JsObject {
+    let scale_x = scale_x.unwrap_or(1.0);
+    self.inner.scale(
+      scale_x,
+      scale_y.unwrap_or(scale_x),
+      scale_z.unwrap_or(1.0),
+      [origin_x.unwrap_or(0.0), origin_y.unwrap_or(0.0), origin_z.unwrap_or(0.0)],
+    );
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn scale3d_self(
+    &mut self,
+    this: This<JsObject>,
+    scale: Option<f64>,
+    origin_x: Option<f64>,
+    origin_y: Option<f64>,
+    origin_z: Option<f64>,
+  ) -> JsObject {
+    let scale = scale.unwrap_or(1.0);
+    self.inner.scale(
+      scale,
+      scale,
+      scale,
+      [origin_x.unwrap_or(0.0), origin_y.unwrap_or(0.0), origin_z.unwrap_or(0.0)],
+    );
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn rotate_self(&mut self, this: This<JsObject>, rot_x: Option<f64>, rot_y: Option<f64>, rot_z: Option<f64>) -> JsObject {
+    self.inner.rotate(rot_x, rot_y, rot_z);
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn rotate_from_vector_self(&mut self, this: This<JsObject>, x: Option<f64>, y: Option<f64>) -> JsObject {
+    self.inner.rotate_from_vector(x.unwrap_or(0.0), y.unwrap_or(0.0));
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn rotate_axis_angle_self(
+    &mut self,
+    this: This<JsObject>,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+    angle: Option<f64>,
+  ) -> JsObject {
+    self.inner.rotate_axis_angle(x.unwrap_or(0.0), y.unwrap_or(0.0), z.unwrap_or(0.0), angle.unwrap_or(0.0));
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn skew_x_self(&mut self, this: This<JsObject>, sx: Option<f64>) -> JsObject {
+    self.inner.skew_x(sx.unwrap_or(0.0));
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn skew_y_self(&mut self, this: This<JsObject>, sy: Option<f64>) -> JsObject {
+    self.inner.skew_y(sy.unwrap_or(0.0));
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn invert_self(&mut self, this: This<JsObject>) -> JsObject {
+    self.inner.invert();
+    this
+  }
+
+  #[napi(ts_return_type = "this")]
+  pub fn set_matrix_value(&mut self, this: This<JsObject>, transform_list: String) -> Result<JsObject> {
+    self.inner = Matrix4::parse(&transform_list)?;
+    Ok(this)
+  }
+}
+
+#[napi]
+pub struct DOMPoint {
+  x: f64,
+  y: f64,
+  z: f64,
+  w: f64,
+}
+
+#[napi]
+impl DOMPoint {
+  #[napi(constructor)]
+  pub fn new(x: Option<f64>, y: Option<f64>, z: Option<f64>, w: Option<f64>) -> Self {
+    Self {
+      x: x.unwrap_or(0.0),
+      y: y.unwrap_or(0.0),
+      z: z.unwrap_or(0.0),
+      w: w.unwrap_or(1.0),
+    }
+  }
+
+  #[napi(factory, ts_args_type = "other?: DOMPointInit")]
+  pub fn from_point(other: Option<DOMPointInit>) -> Self {
+    Self::from_init(other)
+  }
+
+  fn from_init(init: Option<DOMPointInit>) -> Self {
+    match init {
+      Some(init) => Self::new(init.x, init.y, init.z, init.w),
+      None => Self::new(None, None, None, None),
+    }
+  }
+
+  fn transform(&self, matrix: &Matrix4) -> Self {
+    let [x, y, z, w] = matrix.transform_point([self.x, self.y, self.z, self.w]);
+    Self { x, y, z, w }
+  }
+
+  #[napi(getter)]
+  pub fn x(&self) -> f64 {
+    self.x
+  }
+
+  #[napi(setter)]
+  pub fn set_x(&mut self, value: f64) {
+    self.x = value;
+  }
+
+  #[napi(getter)]
+  pub fn y(&self) -> f64 {
+    self.y
+  }
+
+  #[napi(setter)]
+  pub fn set_y(&mut self, value: f64) {
+    self.y = value;
+  }
+
+  #[napi(getter)]
+  pub fn z(&self) -> f64 {
+    self.z
+  }
+
+  #[napi(setter)]
+  pub fn set_z(&mut self, value: f64) {
+    self.z = value;
+  }
+
+  #[napi(getter)]
+  pub fn w(&self) -> f64 {
+    self.w
+  }
+
+  #[napi(setter)]
+  pub fn set_w(&mut self, value: f64) {
+    self.w = value;
+  }
+
+  #[napi(ts_args_type = "matrix?: DOMMatrixInit")]
+  pub fn matrix_transform(&self, matrix: Option<DOMMatrixInit>) -> Result<DOMPoint> {
+    let matrix = match matrix {
+      Some(matrix) => Matrix4::from_init(&matrix)?,
+      None => Matrix4::identity(),
+    };
+
+    Ok(self.transform(&matrix))
+  }
+
+  #[napi(js_name = "toJSON")]
+  pub fn to_json(&self) -> DOMPointInit {
+    DOMPointInit {
+      x: Some(self.x),
+      y: Some(self.y),
+      z: Some(self.z),
+      w: Some(self.w),
+    }
+  }
+}
+
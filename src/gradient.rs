@@ -1,124 +1,204 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use skia_safe::{Color4f, Shader, Point, TileMode, gradient_shader};
+use skia_safe::{Color, Color4f, ColorSpace, Matrix, Shader, Point, TileMode, gradient_shader, shaders};
+use skia_safe::gradient_shader::{interpolation, Interpolation};
 use std::sync::Mutex;
+use crate::color::parse_color;
 
-#[napi(object)]
 pub struct ColorStop {
   pub offset: f64,
-  pub color: String,
+  pub color: Color4f,
 }
 
-#[derive(Default)]
-#[napi]
-pub struct LinearGradient {
-  x0: f64,
-  y0: f64,
-  x1: f64,
-  y1: f64,
-  color_stops: Mutex<Vec<ColorStop>>,
+enum GradientGeometry {
+  Linear {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+  },
+  Radial {
+    x0: f64,
+    y0: f64,
+    r0: f64,
+    x1: f64,
+    y1: f64,
+    r1: f64,
+  },
+  Conic {
+    start_angle: f64,
+    x: f64,
+    y: f64,
+  },
 }
 
-#[derive(Default)]
+// Created through createLinearGradient, createRadialGradient and
+// createConicGradient, so there is no JS constructor
 #[napi]
-pub struct RadialGradient {
-  x0: f64,
-  y0: f64,
-  r0: f64,
-  x1: f64,
-  y1: f64,
-  r1: f64,
+pub struct CanvasGradient {
+  geometry: GradientGeometry,
   color_stops: Mutex<Vec<ColorStop>>,
+  // Non-standard extensions, the defaults match the canvas spec
+  tile_mode: TileMode,
+  interpolation: Interpolation,
 }
 
 #[napi]
-impl RadialGradient {
-  #[napi(constructor)]
-  pub fn new(x0: f64, y0: f64, r0: f64, x1: f64, y1: f64, r1: f64) -> Self {
+impl CanvasGradient {
+  pub(crate) fn new_linear(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+    Self::new(GradientGeometry::Linear { x0, y0, x1, y1 })
+  }
+
+  pub(crate) fn new_radial(x0: f64, y0: f64, r0: f64, x1: f64, y1: f64, r1: f64) -> Self {
+    Self::new(GradientGeometry::Radial { x0, y0, r0, x1, y1, r1 })
+  }
+
+  pub(crate) fn new_conic(start_angle: f64, x: f64, y: f64) -> Self {
+    Self::new(GradientGeometry::Conic { start_angle, x, y })
+  }
+
+  fn new(geometry: GradientGeometry) -> Self {
     Self {
-      x0,
-      y0,
-      r0,
-      x1,
-      y1,
-      r1,
+      geometry,
       color_stops: Mutex::new(Vec::new()),
+      tile_mode: TileMode::Clamp,
+      interpolation: DEFAULT_INTERPOLATION,
     }
   }
 
-  #[napi]
-  pub fn add_color_stop(&self, offset: f64, color: String) -> Result<()> {
-    let mut color_stops = self.color_stops.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock color_stops mutex")
-    })?;
-
-    color_stops.push(ColorStop { offset, color });
+  // Extension: how the gradient continues beyond its first and last stops,
+  // one of "clamp" (the default), "repeat", "mirror" or "decal"
+  #[napi(getter)]
+  pub fn tile_mode(&self) -> String {
+    match self.tile_mode {
+      TileMode::Repeat => "repeat",
+      TileMode::Mirror => "mirror",
+      TileMode::Decal => "decal",
+      _ => "clamp",
+    }
+    .to_string()
+  }
 
-    Ok(())
+  #[napi(setter)]
+  pub fn set_tile_mode(&mut self, value: String) {
+    // Invalid values are ignored, like other enumerated canvas attributes
+    self.tile_mode = match value.as_str() {
+      "clamp" => TileMode::Clamp,
+      "repeat" => TileMode::Repeat,
+      "mirror" => TileMode::Mirror,
+      "decal" => TileMode::Decal,
+      _ => return,
+    };
   }
 
-  // Internal method to create a Skia shader
-  pub(crate) fn create_shader(&self) -> Result<Shader> {
-    let color_stops = self.color_stops.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock color_stops mutex")
-    })?;
+  // Extension: the color space stops are interpolated in, following the CSS
+  // Color 4 interpolation method syntax without the leading "in", e.g.
+  // "oklab", "srgb-linear" or "oklch longer hue". Until set it is null, and
+  // stops are interpolated unpremultiplied in the canvas color space as the
+  // canvas spec requires, while an explicit space interpolates premultiplied
+  // colors as CSS gradients do. Setting null restores the default.
+  #[napi(getter)]
+  pub fn interpolation(&self) -> Option<String> {
+    use interpolation::{ColorSpace as Space, HueMethod};
 
-    if color_stops.is_empty() {
-      return Err(Error::new(Status::InvalidArg, "No color stops defined"));
-    }
+    let space = match self.interpolation.color_space {
+      Space::Destination => return None,
+      Space::SRGBLinear => "srgb-linear",
+      Space::Lab => "lab",
+      Space::LCH => "lch",
+      Space::OKLab => "oklab",
+      Space::OKLCH => "oklch",
+      Space::HSL => "hsl",
+      Space::HWB => "hwb",
+      _ => "srgb",
+    };
 
-    // Convert color stops to Skia format
-    let mut colors = Vec::with_capacity(color_stops.len());
-    let mut positions = Vec::with_capacity(color_stops.len());
+    let hue_method = match self.interpolation.hue_method {
+      HueMethod::Longer => "longer",
+      HueMethod::Increasing => "increasing",
+      HueMethod::Decreasing => "decreasing",
+      _ => "shorter",
+    };
 
-    // This is a simplified implementation - in a real library we'd need to parse color strings
-    for stop in color_stops.iter() {
-      colors.push(Color4f::new(0.0, 0.0, 0.0, 1.0)); // Black as default
-      positions.push(stop.offset as f32);
+    if is_polar(self.interpolation.color_space) && hue_method != "shorter" {
+      Some(format!("{} {} hue", space, hue_method))
+    } else {
+      Some(space.to_string())
     }
+  }
 
-    // For simplicity, we'll just use a two-point conical gradient
-    let center1 = Point::new(self.x0 as f32, self.y0 as f32);
-    let center2 = Point::new(self.x1 as f32, self.y1 as f32);
-
-    let shader = gradient_shader::two_point_conical(
-      center1,
-      self.r0 as f32,
-      center2,
-      self.r1 as f32,
-      colors.as_slice(),
-      Some(positions.as_slice()),
-      TileMode::Clamp,
-      None,
-      None,
-    ).ok_or_else(|| {
-      Error::new(Status::GenericFailure, "Failed to create radial gradient shader")
-    })?;
+  #[napi(setter)]
+  pub fn set_interpolation(&mut self, value: Option<String>) {
+    use interpolation::{ColorSpace as Space, HueMethod};
 
-    Ok(shader)
-  }
-}
+    let Some(value) = value else {
+      self.interpolation = DEFAULT_INTERPOLATION;
+      return;
+    };
 
-#[napi]
-impl LinearGradient {
-  #[napi(constructor)]
-  pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
-    Self {
-      x0,
-      y0,
-      x1,
-      y1,
-      color_stops: Mutex::new(Vec::new()),
-    }
+    let mut words = value.split_whitespace();
+
+    let color_space = match words.next() {
+      Some("srgb") => Space::SRGB,
+      Some("srgb-linear") => Space::SRGBLinear,
+      Some("lab") => Space::Lab,
+      Some("lch") => Space::LCH,
+      Some("oklab") => Space::OKLab,
+      Some("oklch") => Space::OKLCH,
+      Some("hsl") => Space::HSL,
+      Some("hwb") => Space::HWB,
+      _ => return,
+    };
+
+    // A hue interpolation method is only valid for polar color spaces
+    let hue_method = match (words.next(), words.next(), words.next()) {
+      (None, None, None) => HueMethod::Shorter,
+      (Some(method), Some("hue"), None) if is_polar(color_space) => match method {
+        "shorter" => HueMethod::Shorter,
+        "longer" => HueMethod::Longer,
+        "increasing" => HueMethod::Increasing,
+        "decreasing" => HueMethod::Decreasing,
+        _ => return,
+      },
+      _ => return,
+    };
+
+    self.interpolation.color_space = color_space;
+    self.interpolation.hue_method = hue_method;
+    self.interpolation.in_premul = interpolation::InPremul::Yes;
   }
 
   #[napi]
   pub fn add_color_stop(&self, offset: f64, color: String) -> Result<()> {
+    if !offset.is_finite() {
+      return Err(Error::new(
+        Status::InvalidArg,
+        "TypeError: The offset provided is not a finite number",
+      ));
+    }
+
+    if !(0.0..=1.0).contains(&offset) {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("IndexSizeError: The offset provided ({}) is outside the range [0, 1]", offset),
+      ));
+    }
+
+    let color = parse_color(&color).ok_or_else(|| {
+      Error::new(
+        Status::InvalidArg,
+        format!("SyntaxError: The value provided ('{}') could not be parsed as a color", color),
+      )
+    })?;
+
     let mut color_stops = self.color_stops.lock().map_err(|_| {
       Error::new(Status::GenericFailure, "Failed to lock color_stops mutex")
     })?;
 
-    color_stops.push(ColorStop { offset, color });
+    // Insert after any stops with the same offset, so stops sharing an
+    // offset produce a hard transition in insertion order
+    let index = color_stops.partition_point(|stop| stop.offset <= offset);
+    color_stops.insert(index, ColorStop { offset, color });
 
     Ok(())
   }
@@ -129,34 +209,81 @@ impl LinearGradient {
       Error::new(Status::GenericFailure, "Failed to lock color_stops mutex")
     })?;
 
+    // A gradient without color stops is transparent black
     if color_stops.is_empty() {
-      return Err(Error::new(Status::InvalidArg, "No color stops defined"));
+      return Ok(shaders::color(Color::TRANSPARENT));
     }
 
-    // Convert color stops to Skia format
-    let mut colors = Vec::with_capacity(color_stops.len());
-    let mut positions = Vec::with_capacity(color_stops.len());
+    let (colors, positions): (Vec<Color4f>, Vec<f32>) = color_stops
+      .iter()
+      .map(|stop| (stop.color, stop.offset as f32))
+      .unzip();
 
-    // This is a simplified implementation - in a real library we'd need to parse color strings
-    for stop in color_stops.iter() {
-      colors.push(Color4f::new(0.0, 0.0, 0.0, 1.0)); // Black as default
-      positions.push(stop.offset as f32);
-    }
+    let shader = match self.geometry {
+      GradientGeometry::Linear { x0, y0, x1, y1 } => {
+        // The gradient paints nothing when the start and end points coincide
+        if x0 == x1 && y0 == y1 {
+          return Ok(shaders::empty());
+        }
 
-    let point1 = Point::new(self.x0 as f32, self.y0 as f32);
-    let point2 = Point::new(self.x1 as f32, self.y1 as f32);
-
-    let shader = gradient_shader::linear(
-      (point1, point2),
-      colors.as_slice(),
-      Some(positions.as_slice()),
-      TileMode::Clamp,
-      None,
-      None,
-    ).ok_or_else(|| {
-      Error::new(Status::GenericFailure, "Failed to create linear gradient shader")
-    })?;
+        gradient_shader::linear_with_interpolation(
+          (Point::new(x0 as f32, y0 as f32), Point::new(x1 as f32, y1 as f32)),
+          (colors.as_slice(), None::<ColorSpace>),
+          Some(positions.as_slice()),
+          self.tile_mode,
+          self.interpolation,
+          None,
+        )
+      },
+      GradientGeometry::Radial { x0, y0, r0, x1, y1, r1 } => {
+        // The gradient paints nothing when both circles are the same
+        if x0 == x1 && y0 == y1 && r0 == r1 {
+          return Ok(shaders::empty());
+        }
 
-    Ok(shader)
+        // Canvas radial gradients are two-point conical gradients in Skia
+        gradient_shader::two_point_conical_with_interpolation(
+          (Point::new(x0 as f32, y0 as f32), r0 as f32),
+          (Point::new(x1 as f32, y1 as f32), r1 as f32),
+          (colors.as_slice(), None::<ColorSpace>),
+          Some(positions.as_slice()),
+          self.tile_mode,
+          self.interpolation,
+          None,
+        )
+      },
+      GradientGeometry::Conic { start_angle, x, y } => {
+        // Skia sweeps clockwise from the positive x axis, like canvas, so the
+        // start angle becomes a rotation around the center
+        let center = Point::new(x as f32, y as f32);
+        let rotation = Matrix::rotate_deg_pivot(start_angle.to_degrees() as f32, center);
+
+        gradient_shader::sweep_with_interpolation(
+          center,
+          (colors.as_slice(), None::<ColorSpace>),
+          Some(positions.as_slice()),
+          self.tile_mode,
+          None,
+          self.interpolation,
+          &rotation,
+        )
+      },
+    };
+
+    shader.ok_or_else(|| {
+      Error::new(Status::GenericFailure, "Failed to create gradient shader")
+    })
   }
 }
+
+const DEFAULT_INTERPOLATION: Interpolation = Interpolation {
+  in_premul: interpolation::InPremul::No,
+  color_space: interpolation::ColorSpace::Destination,
+  hue_method: interpolation::HueMethod::Shorter,
+};
+
+fn is_polar(color_space: interpolation::ColorSpace) -> bool {
+  use interpolation::ColorSpace as Space;
+
+  matches!(color_space, Space::LCH | Space::OKLCH | Space::HSL | Space::HWB)
+}
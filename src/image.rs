@@ -0,0 +1,125 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use skia_safe::{Data, Image as SkImage};
+use crate::canvas::Canvas;
+use crate::image_data::ImageData;
+
+// Sources accepted by createPattern and createImageBitmap
+pub(crate) type ImageSource<'a> = Either4<
+  &'a Canvas,
+  &'a Image,
+  &'a ImageData,
+  &'a ImageBitmap,
+>;
+
+// Returns None when the source has nothing to draw yet, e.g. an Image without
+// a decoded src
+pub(crate) fn source_image(source: &ImageSource) -> Result<Option<SkImage>> {
+  match source {
+    Either4::A(canvas) => canvas.snapshot().map(Some),
+    Either4::B(image) => Ok(image.image.clone()),
+    Either4::C(image_data) => image_data.to_skia_image().map(Some),
+    Either4::D(bitmap) => match &bitmap.image {
+      Some(image) => Ok(Some(image.clone())),
+      None => Err(Error::new(
+        Status::InvalidArg,
+        "InvalidStateError: The image bitmap has been closed",
+      )),
+    },
+  }
+}
+
+// A decoded image. Unlike browsers, setting src takes the encoded bytes and
+// decodes them synchronously.
+#[napi]
+pub struct Image {
+  src: Option<Buffer>,
+  image: Option<SkImage>,
+}
+
+#[napi]
+impl Image {
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    Self {
+      src: None,
+      image: None,
+    }
+  }
+
+  #[napi(getter)]
+  pub fn src(&self) -> Option<Buffer> {
+    self.src.clone()
+  }
+
+  #[napi(setter)]
+  pub fn set_src(&mut self, value: Buffer) -> Result<()> {
+    self.image = SkImage::from_encoded(Data::new_copy(&value));
+    self.src = Some(value);
+
+    if self.image.is_none() {
+      return Err(Error::new(Status::InvalidArg, "Failed to decode image"));
+    }
+
+    Ok(())
+  }
+
+  #[napi(getter)]
+  pub fn width(&self) -> u32 {
+    self.image.as_ref().map_or(0, |image| image.width() as u32)
+  }
+
+  #[napi(getter)]
+  pub fn height(&self) -> u32 {
+    self.image.as_ref().map_or(0, |image| image.height() as u32)
+  }
+
+  #[napi(getter)]
+  pub fn complete(&self) -> bool {
+    self.image.is_some()
+  }
+}
+
+impl Default for Image {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// Created through createImageBitmap, so there is no JS constructor
+#[napi]
+pub struct ImageBitmap {
+  image: Option<SkImage>,
+}
+
+#[napi]
+impl ImageBitmap {
+  #[napi(getter)]
+  pub fn width(&self) -> u32 {
+    self.image.as_ref().map_or(0, |image| image.width() as u32)
+  }
+
+  #[napi(getter)]
+  pub fn height(&self) -> u32 {
+    self.image.as_ref().map_or(0, |image| image.height() as u32)
+  }
+
+  #[napi]
+  pub fn close(&mut self) {
+    self.image = None;
+  }
+}
+
+// Unlike browsers, the bitmap is created synchronously rather than through a
+// Promise. Awaiting the result still works.
+#[napi(ts_args_type = "image: Canvas | Image | ImageData | ImageBitmap")]
+pub fn create_image_bitmap(image: ImageSource) -> Result<ImageBitmap> {
+  let image = source_image(&image)?.ok_or_else(|| {
+    Error::new(
+      Status::InvalidArg,
+      "InvalidStateError: The image has not been decoded",
+    )
+  })?;
+
+  Ok(ImageBitmap { image: Some(image) })
+}
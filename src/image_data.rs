@@ -1,6 +1,6 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use skia_safe::{Bitmap as SkBitmap, AlphaType, ImageInfo};
+use skia_safe::{images, Bitmap as SkBitmap, AlphaType, ColorType, Data, Image as SkImage, ImageInfo};
 
 #[napi(js_name = "ImageData")]
 pub struct ImageData {
@@ -73,41 +73,20 @@ impl ImageData {
     self.data.clone()
   }
 
-  // Internal method to convert to Skia bitmap
-  pub(crate) fn to_skia_bitmap(&self) -> Result<SkBitmap> {
-    let mut bitmap = SkBitmap::new();
-
-    let info = ImageInfo::new_n32(
+  // Internal method to copy the pixels into a Skia image. ImageData holds
+  // unpremultiplied RGBA, so the image is described that way.
+  pub(crate) fn to_skia_image(&self) -> Result<SkImage> {
+    let info = ImageInfo::new(
       (self.width as i32, self.height as i32),
-      AlphaType::Premul,
+      ColorType::RGBA8888,
+      AlphaType::Unpremul,
       None,
     );
 
-    let success = bitmap.set_info(&info, None);
-    if !success {
-      return Err(Error::new(Status::GenericFailure, "Failed to set bitmap info"));
-    }
-    // In newer skia-safe, we need to handle the bool return
-    bitmap.alloc_pixels();
-
-    // Check if allocation was successful
-    if bitmap.is_null() {
-      return Err(Error::new(Status::GenericFailure, "Failed to allocate pixels"));
-    }
-
-    // In newer version, install_pixels takes different parameters
-    unsafe {
-      let pixel_ptr = self.data.as_ptr() as *mut std::ffi::c_void;
-      bitmap.install_pixels(
-        &info,
-        pixel_ptr,
-        info.min_row_bytes(),
-      );
-    }
-
-    // No need for pixels copying, install_pixels does it
-
-    Ok(bitmap)
+    let row_bytes = info.min_row_bytes();
+    images::raster_from_data(&info, Data::new_copy(&self.data), row_bytes).ok_or_else(|| {
+      Error::new(Status::GenericFailure, "Failed to create image from image data")
+    })
   }
 
   // Internal method to create from Skia bitmap
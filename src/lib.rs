@@ -3,17 +3,18 @@
 use napi_derive::napi;
 
 pub mod canvas;
+pub mod color;
 pub mod context_2d;
+pub mod dom_matrix;
 pub mod gradient;
-pub mod color_stop;
+pub mod image;
 pub mod pattern;
 pub mod path2d;
+pub mod style;
 pub mod image_data;
-pub mod linear_gradient;
-pub mod radial_gradient;
 
-// Re-export the gradient types for easier access
-pub use gradient::{LinearGradient, RadialGradient};
+// Re-export the gradient type for easier access
+pub use gradient::CanvasGradient;
 
 #[napi]
 pub fn version() -> String {
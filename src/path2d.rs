@@ -1,6 +1,7 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use skia_safe::{Path as SkPath};
+use crate::dom_matrix::DOMMatrix2DInit;
 
 #[napi(js_name = "Path2D")]
 pub struct Path2D {
@@ -9,6 +10,11 @@ pub struct Path2D {
 
 #[napi]
 impl Path2D {
+  // Internal method to get the Skia path, in user space
+  pub(crate) fn sk_path(&self) -> &SkPath {
+    &self.path
+  }
+
   #[napi(constructor)]
   pub fn new(path: Option<&Path2D>) -> Result<Self> {
     match path {
@@ -24,10 +30,18 @@ impl Path2D {
     }
   }
 
-  #[napi]
-  pub fn add_path(&mut self, path: &Path2D) -> Result<()> {
-    // In the newer version, the API might have changed, so we'll use just the basic add_path
-    self.path.add_path(&path.path, (0.0, 0.0), None);
+  #[napi(ts_args_type = "path: Path2D, transform?: DOMMatrix2DInit")]
+  pub fn add_path(&mut self, path: &Path2D, transform: Option<DOMMatrix2DInit>) -> Result<()> {
+    let Some(transform) = transform else {
+      self.path.add_path(&path.path, (0.0, 0.0), None);
+      return Ok(());
+    };
+
+    // Nothing is added when the matrix has non-finite values
+    if let Some(matrix) = transform.to_matrix()? {
+      self.path.add_path_matrix(&path.path, &matrix, None);
+    }
+
     Ok(())
   }
 
@@ -1,8 +1,8 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use skia_safe::{Bitmap as SkBitmap, Shader, TileMode};
+use skia_safe::{Image as SkImage, Matrix, SamplingOptions, Shader, TileMode};
+use crate::dom_matrix::DOMMatrix2DInit;
 use crate::image_data::ImageData;
-use std::sync::Mutex;
 
 #[napi(string_enum)]
 pub enum RepeatPattern {
@@ -12,69 +12,70 @@ pub enum RepeatPattern {
   NoRepeat,
 }
 
-struct CanvasPatternPrivate {
-  bitmap: Mutex<SkBitmap>,
-  repeat: Mutex<RepeatPattern>,
-}
-
 #[napi]
 pub struct CanvasPattern {
-  inner: CanvasPatternPrivate,
+  image: SkImage,
+  repeat: RepeatPattern,
+  transform: Matrix,
 }
 
 #[napi]
 impl CanvasPattern {
+  // Kept for compatibility, ctx.createPattern() accepts more sources
   #[napi(constructor)]
   pub fn new(image_data: &ImageData, repeat_pattern: Option<String>) -> Result<Self> {
-    let bitmap = image_data.to_skia_bitmap()?;
+    Self::from_image(image_data.to_skia_image()?, repeat_pattern)
+  }
 
-    let repeat = match repeat_pattern.as_deref() {
-      Some("repeat") | None => RepeatPattern::Repeat,
+  // The image is a copy, so later changes to the source don't affect the pattern
+  pub(crate) fn from_image(image: SkImage, repetition: Option<String>) -> Result<Self> {
+    // Both null and the empty string mean "repeat"
+    let repeat = match repetition.as_deref() {
+      Some("repeat") | Some("") | None => RepeatPattern::Repeat,
       Some("repeat-x") => RepeatPattern::RepeatX,
       Some("repeat-y") => RepeatPattern::RepeatY,
       Some("no-repeat") => RepeatPattern::NoRepeat,
-      _ => {
+      Some(other) => {
         return Err(Error::new(
           Status::InvalidArg,
-          format!("Invalid repeat pattern: {:?}", repeat_pattern),
+          format!("SyntaxError: The provided repetition ('{}') is not valid", other),
         ));
       }
     };
 
-    Ok(Self {
-      inner: CanvasPatternPrivate {
-        bitmap: Mutex::new(bitmap),
-        repeat: Mutex::new(repeat),
-      }
-    })
+    Ok(Self { image, repeat, transform: Matrix::default() })
   }
 
-  // Internal method to create a Skia shader
-  pub(crate) fn create_shader(&self) -> Result<Shader> {
-    let bitmap = self.inner.bitmap.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock bitmap mutex")
-    })?;
+  // Sets the pattern space, independent of the context transform. Omitting
+  // the matrix resets it to the identity.
+  #[napi(ts_args_type = "transform?: DOMMatrix2DInit")]
+  pub fn set_transform(&mut self, transform: Option<DOMMatrix2DInit>) -> Result<()> {
+    let Some(transform) = transform else {
+      self.transform = Matrix::default();
+      return Ok(());
+    };
+
+    // Matrices with non-finite values are ignored
+    if let Some(matrix) = transform.to_matrix()? {
+      self.transform = matrix;
+    }
 
-    let repeat = self.inner.repeat.lock().map_err(|_| {
-      Error::new(Status::GenericFailure, "Failed to lock repeat mutex")
-    })?;
+    Ok(())
+  }
 
-    let (tile_x, tile_y) = match *repeat {
+  // Internal method to create a Skia shader
+  pub(crate) fn create_shader(&self, sampling: SamplingOptions) -> Result<Shader> {
+    // Areas outside a non-repeating direction are transparent, which is
+    // Decal rather than Clamp
+    let (tile_x, tile_y) = match self.repeat {
       RepeatPattern::Repeat => (TileMode::Repeat, TileMode::Repeat),
-      RepeatPattern::RepeatX => (TileMode::Repeat, TileMode::Clamp),
-      RepeatPattern::RepeatY => (TileMode::Clamp, TileMode::Repeat),
-      RepeatPattern::NoRepeat => (TileMode::Clamp, TileMode::Clamp),
+      RepeatPattern::RepeatX => (TileMode::Repeat, TileMode::Decal),
+      RepeatPattern::RepeatY => (TileMode::Decal, TileMode::Repeat),
+      RepeatPattern::NoRepeat => (TileMode::Decal, TileMode::Decal),
     };
 
-    let image = bitmap.as_image();
-    let shader = image.to_shader(
-      (tile_x, tile_y),
-      skia_safe::SamplingOptions::default(),
-      None,
-    ).ok_or_else(|| {
+    self.image.to_shader((tile_x, tile_y), sampling, &self.transform).ok_or_else(|| {
       Error::new(Status::GenericFailure, "Failed to create shader from image")
-    })?;
-
-    Ok(shader)
+    })
   }
 }
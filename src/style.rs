@@ -0,0 +1,81 @@
+use napi::bindgen_prelude::*;
+use napi::NapiRaw;
+use skia_safe::{Color4f, Paint, SamplingOptions};
+use crate::color::{parse_color, serialize_color};
+use crate::gradient::CanvasGradient;
+use crate::pattern::CanvasPattern;
+
+// Values accepted by the fillStyle and strokeStyle setters
+pub(crate) type StyleInput = Either3<
+  String,
+  ClassInstance<CanvasGradient>,
+  ClassInstance<CanvasPattern>,
+>;
+
+// Values returned by the fillStyle and strokeStyle getters
+pub(crate) type StyleOutput = Either3<
+  String,
+  Reference<CanvasGradient>,
+  Reference<CanvasPattern>,
+>;
+
+// A fill or stroke style. Gradients and patterns are kept as references to
+// their JS objects so the getters hand back the same object and later
+// changes such as new color stops are picked up.
+pub(crate) enum CanvasStyle {
+  Color(Color4f),
+  Gradient(Reference<CanvasGradient>),
+  Pattern(Reference<CanvasPattern>),
+}
+
+impl CanvasStyle {
+  // Returns None for strings that are not valid colors, which the setters ignore
+  pub(crate) fn from_input(env: Env, value: StyleInput) -> Result<Option<Self>> {
+    Ok(Some(match value {
+      Either3::A(color) => match parse_color(&color) {
+        Some(color) => CanvasStyle::Color(color),
+        None => return Ok(None),
+      },
+      Either3::B(gradient) => CanvasStyle::Gradient(to_reference(env, gradient)?),
+      Either3::C(pattern) => CanvasStyle::Pattern(to_reference(env, pattern)?),
+    }))
+  }
+
+  // Gradients and patterns are shared rather than copied, as in browsers
+  pub(crate) fn clone_with_env(&self, env: Env) -> Result<Self> {
+    Ok(match self {
+      CanvasStyle::Color(color) => CanvasStyle::Color(*color),
+      CanvasStyle::Gradient(gradient) => CanvasStyle::Gradient(gradient.clone(env)?),
+      CanvasStyle::Pattern(pattern) => CanvasStyle::Pattern(pattern.clone(env)?),
+    })
+  }
+
+  pub(crate) fn to_output(&self, env: Env) -> Result<StyleOutput> {
+    Ok(match self {
+      CanvasStyle::Color(color) => Either3::A(serialize_color(color)),
+      CanvasStyle::Gradient(gradient) => Either3::B(gradient.clone(env)?),
+      CanvasStyle::Pattern(pattern) => Either3::C(pattern.clone(env)?),
+    })
+  }
+
+  // The sampling options only affect patterns
+  pub(crate) fn apply_to_paint(&self, paint: &mut Paint, sampling: SamplingOptions) -> Result<()> {
+    match self {
+      CanvasStyle::Color(color) => {
+        paint.set_color4f(color, None);
+      },
+      CanvasStyle::Gradient(gradient) => {
+        paint.set_shader(gradient.create_shader()?);
+      },
+      CanvasStyle::Pattern(pattern) => {
+        paint.set_shader(pattern.create_shader(sampling)?);
+      },
+    }
+
+    Ok(())
+  }
+}
+
+fn to_reference<T: 'static>(env: Env, instance: ClassInstance<T>) -> Result<Reference<T>> {
+  unsafe { Reference::from_napi_value(env.raw(), instance.raw()) }
+}
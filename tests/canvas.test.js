@@ -1,6 +1,83 @@
-const { Canvas } = require('../');
+const {
+  Canvas,
+  CanvasGradient,
+  CanvasPattern,
+  CanvasRenderingContext2D,
+  DOMMatrix,
+  DOMMatrixReadOnly,
+  DOMPoint,
+  Image,
+  ImageData,
+  Path2D,
+  createImageBitmap,
+} = require('../');
 const fs = require('fs');
 const path = require('path');
+const v8 = require('v8');
+const vm = require('vm');
+const zlib = require('zlib');
+
+// Decodes the canvas as PNG and returns a function reading [r, g, b, a] at a
+// pixel. Only handles the 8-bit RGB and RGBA images the encoder produces.
+function readPixels(canvas) {
+  const png = canvas.toBuffer('image/png');
+  const chunks = [];
+  let width, height, channels;
+
+  for (let offset = 8; offset < png.length;) {
+    const length = png.readUInt32BE(offset);
+    const type = png.toString('ascii', offset + 4, offset + 8);
+    const data = png.subarray(offset + 8, offset + 8 + length);
+
+    if (type === 'IHDR') {
+      width = data.readUInt32BE(0);
+      height = data.readUInt32BE(4);
+      channels = { 2: 3, 6: 4 }[data[9]];
+      if (data[8] !== 8 || !channels) {
+        throw new Error('Unsupported PNG format');
+      }
+    } else if (type === 'IDAT') {
+      chunks.push(data);
+    }
+
+    offset += length + 12;
+  }
+
+  // Undo the per-row filters
+  const raw = zlib.inflateSync(Buffer.concat(chunks));
+  const stride = width * channels;
+  const pixels = Buffer.alloc(stride * height);
+
+  for (let y = 0; y < height; y++) {
+    const filter = raw[y * (stride + 1)];
+    const line = raw.subarray(y * (stride + 1) + 1, (y + 1) * (stride + 1));
+    const row = y * stride;
+
+    for (let i = 0; i < stride; i++) {
+      const a = i >= channels ? pixels[row + i - channels] : 0;
+      const b = y > 0 ? pixels[row - stride + i] : 0;
+      const c = i >= channels && y > 0 ? pixels[row - stride + i - channels] : 0;
+      const p = a + b - c;
+      const paeth = [a, b, c].sort((u, v) => Math.abs(p - u) - Math.abs(p - v))[0];
+      const predictor = [0, a, b, (a + b) >> 1, paeth][filter];
+      pixels[row + i] = (line[i] + predictor) & 0xff;
+    }
+  }
+
+  return (x, y) => {
+    const start = (y * width + x) * channels;
+    const pixel = [...pixels.subarray(start, start + channels)];
+    return channels === 4 ? pixel : [...pixel, 255];
+  };
+}
+
+// Compares pixels allowing for interpolation and antialiasing rounding
+function expectPixelClose(actual, expected, tolerance = 5) {
+  const difference = Math.max(...actual.map((value, i) => Math.abs(value - expected[i])));
+  if (difference > tolerance) {
+    expect(actual).toEqual(expected);
+  }
+}
 
 describe('Canvas API', () => {
   const outputDir = path.join(__dirname, 'output');
@@ -93,6 +170,71 @@ describe('Canvas API', () => {
     expect(buffer.length).toBeGreaterThan(0);
   });
 
+  test('parse CSS colors', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const colors = [
+      'rebeccapurple',
+      '#0f0',
+      '#00ff0080',
+      'rgb(255 0 0 / 50%)',
+      'rgba(0, 0, 255, 0.5)',
+      'hsl(120deg 100% 25%)',
+      'hwb(200 10% 20%)',
+      'lab(50% 40 -20)',
+      'lch(60 50 30)',
+      'oklab(0.6 0.1 -0.1)',
+      'oklch(70% 0.15 140)',
+      'color(display-p3 1 0.5 0)',
+    ];
+
+    colors.forEach((color, i) => {
+      ctx.fillStyle = 'transparent';
+      ctx.fillStyle = color;
+      expect(ctx.fillStyle).not.toBe('rgba(0, 0, 0, 0)');
+      ctx.fillRect(i * 15, 0, 15, 100);
+    });
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'colors.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('ignore invalid colors', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    ctx.fillStyle = 'red';
+    ctx.fillStyle = 'not-a-color';
+    expect(ctx.fillStyle).toBe('#ff0000');
+
+    ctx.strokeStyle = 'blue';
+    ctx.strokeStyle = 'rgb(1, 2)';
+    expect(ctx.strokeStyle).toBe('#0000ff');
+  });
+
+  test('serialize colors', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.fillStyle).toBe('#000000');
+
+    ctx.fillStyle = 'hsl(120, 100%, 50%)';
+    expect(ctx.fillStyle).toBe('#00ff00');
+
+    ctx.fillStyle = 'rgba(255, 0, 0, 0.5)';
+    expect(ctx.fillStyle).toBe('rgba(255, 0, 0, 0.5)');
+
+    ctx.strokeStyle = 'transparent';
+    expect(ctx.strokeStyle).toBe('rgba(0, 0, 0, 0)');
+
+    const gradient = ctx.createLinearGradient(0, 0, 200, 0);
+    ctx.setLinearGradientFillStyle(gradient);
+    expect(ctx.fillStyle).toBe(gradient);
+  });
+
   test('gradient', () => {
     const canvas = new Canvas(200, 100);
     const ctx = canvas.getContext2D();
@@ -112,4 +254,1081 @@ describe('Canvas API', () => {
 
     expect(buffer.length).toBeGreaterThan(0);
   });
+
+  test('gradients share a single class', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const linear = ctx.createLinearGradient(0, 0, 200, 0);
+    const radial = ctx.createRadialGradient(100, 50, 0, 100, 50, 50);
+    expect(linear).toBeInstanceOf(CanvasGradient);
+    expect(radial).toBeInstanceOf(CanvasGradient);
+
+    expect(() => ctx.createRadialGradient(0, 0, -1, 0, 0, 10)).toThrow(/IndexSizeError/);
+  });
+
+  test('conic gradient', () => {
+    const canvas = new Canvas(200, 200);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createConicGradient(-Math.PI / 2, 100, 100);
+    expect(gradient).toBeInstanceOf(CanvasGradient);
+    gradient.addColorStop(0, 'red');
+    gradient.addColorStop(0.25, 'yellow');
+    gradient.addColorStop(0.25, 'green');
+    gradient.addColorStop(1, 'blue');
+
+    ctx.fillStyle = gradient;
+    ctx.beginPath();
+    ctx.arc(100, 100, 80, 0, Math.PI * 2);
+    ctx.fill();
+
+    expect(() => ctx.createConicGradient(NaN, 0, 0)).toThrow(/TypeError/);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'conic-gradient.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('gradient tile mode and interpolation', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createLinearGradient(0, 0, 50, 0);
+    gradient.addColorStop(0, 'blue');
+    gradient.addColorStop(1, 'yellow');
+
+    expect(gradient.tileMode).toBe('clamp');
+    expect(gradient.interpolation).toBeNull();
+
+    gradient.tileMode = 'mirror';
+    gradient.tileMode = 'sideways';
+    expect(gradient.tileMode).toBe('mirror');
+
+    gradient.interpolation = 'oklch longer hue';
+    expect(gradient.interpolation).toBe('oklch longer hue');
+    gradient.interpolation = 'oklab longer hue';
+    expect(gradient.interpolation).toBe('oklch longer hue');
+
+    ctx.fillStyle = gradient;
+    ctx.fillRect(0, 0, 200, 100);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'gradient-extensions.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('validate gradient color stops', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createLinearGradient(0, 0, 200, 0);
+    expect(() => gradient.addColorStop(1.5, 'red')).toThrow(/IndexSizeError/);
+    expect(() => gradient.addColorStop(-0.1, 'red')).toThrow(/IndexSizeError/);
+    expect(() => gradient.addColorStop(0.5, 'not-a-color')).toThrow(/SyntaxError/);
+
+    // Stops sharing an offset produce a hard transition
+    gradient.addColorStop(0.5, 'blue');
+    gradient.addColorStop(0, 'red');
+    gradient.addColorStop(0.5, 'lime');
+    gradient.addColorStop(1, 'yellow');
+
+    ctx.fillStyle = gradient;
+    ctx.fillRect(0, 0, 200, 100);
+
+    fs.writeFileSync(path.join(outputDir, 'gradient-stops.png'), canvas.toBuffer('image/png'));
+
+    // Stops are sorted by offset, and blue stays before lime at 0.5
+    const pixel = readPixels(canvas);
+    expectPixelClose(pixel(1, 50), [255, 0, 0, 255]);
+    expectPixelClose(pixel(50, 50), [128, 0, 128, 255]);
+    expectPixelClose(pixel(98, 50), [0, 0, 255, 255]);
+    expectPixelClose(pixel(101, 50), [0, 255, 0, 255]);
+    expectPixelClose(pixel(150, 50), [128, 255, 0, 255]);
+    expectPixelClose(pixel(199, 50), [255, 255, 0, 255]);
+  });
+
+  test('fill with gradient and pattern', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createLinearGradient(0, 0, 200, 0);
+    gradient.addColorStop(0, 'red');
+    gradient.addColorStop(1, 'blue');
+
+    ctx.fillStyle = gradient;
+    expect(ctx.fillStyle).toBe(gradient);
+    ctx.fillRect(0, 0, 100, 100);
+
+    const imageData = ImageData.fromBuffer(Buffer.alloc(4 * 4 * 4, 128), 4, 4);
+    const pattern = new CanvasPattern(imageData, 'repeat');
+
+    ctx.fillStyle = pattern;
+    expect(ctx.fillStyle).toBe(pattern);
+    ctx.fillRect(100, 0, 100, 100);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'fill-style-objects.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('stroke with gradient', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createRadialGradient(100, 50, 0, 100, 50, 80);
+    gradient.addColorStop(0, 'orange');
+    gradient.addColorStop(1, 'purple');
+
+    ctx.strokeStyle = gradient;
+    expect(ctx.strokeStyle).toBe(gradient);
+
+    ctx.lineWidth = 10;
+    ctx.strokeRect(20, 20, 160, 60);
+    ctx.strokeText('Skinvas', 60, 55);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'stroke-gradient.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('create patterns from image sources', () => {
+    const source = new Canvas(10, 10);
+    const sourceCtx = source.getContext2D();
+    sourceCtx.fillStyle = 'red';
+    sourceCtx.fillRect(0, 0, 5, 5);
+
+    const image = new Image();
+    expect(image.complete).toBe(false);
+    image.src = source.toBuffer('image/png');
+    expect(image.complete).toBe(true);
+    expect(image.width).toBe(10);
+    expect(image.height).toBe(10);
+
+    const imageData = ImageData.fromBuffer(Buffer.alloc(4 * 4 * 4, 255), 4, 4);
+    const bitmap = createImageBitmap(imageData);
+    expect(bitmap.width).toBe(4);
+
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+    expect(ctx.imageSmoothingEnabled).toBe(true);
+    expect(ctx.imageSmoothingQuality).toBe('low');
+    ctx.imageSmoothingEnabled = false;
+    ctx.imageSmoothingQuality = 'high';
+    ctx.imageSmoothingQuality = 'best';
+    expect(ctx.imageSmoothingQuality).toBe('high');
+
+    for (const [i, source_] of [source, image, imageData, bitmap].entries()) {
+      const pattern = ctx.createPattern(source_, i % 2 ? 'no-repeat' : null);
+      expect(pattern).toBeInstanceOf(CanvasPattern);
+      ctx.fillStyle = pattern;
+      ctx.fillRect(i * 50, 0, 50, 100);
+    }
+
+    expect(ctx.createPattern(source, '')).toBeInstanceOf(CanvasPattern);
+    expect(ctx.createPattern(new Image(), 'repeat')).toBeNull();
+    expect(() => ctx.createPattern(source, 'repeat-xy')).toThrow(/SyntaxError/);
+
+    bitmap.close();
+    expect(bitmap.width).toBe(0);
+    expect(() => ctx.createPattern(bitmap, 'repeat')).toThrow(/InvalidStateError/);
+    expect(() => { image.src = Buffer.from('not an image'); }).toThrow();
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'patterns.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('pattern transform', () => {
+    const imageData = ImageData.fromBuffer(Buffer.alloc(2 * 2 * 4, 200), 2, 2);
+
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+    const pattern = ctx.createPattern(imageData, 'repeat');
+
+    pattern.setTransform({ a: 4, d: 4, e: 1, f: 1 });
+    pattern.setTransform({ m11: 2, m22: 2, m41: 5 });
+    pattern.setTransform({ a: 2, m11: 2 });
+    pattern.setTransform({ a: NaN });
+    pattern.setTransform();
+    expect(() => pattern.setTransform({ a: 1, m11: 2 })).toThrow(/TypeError/);
+
+    pattern.setTransform({ a: 10, d: 10 });
+    ctx.fillStyle = pattern;
+    ctx.fillRect(0, 0, 100, 100);
+
+    fs.writeFileSync(path.join(outputDir, 'pattern-transform.png'), canvas.toBuffer('image/png'));
+
+    // A red square in the corner of a transparent tile follows the matrix
+    const tile = new Canvas(10, 10);
+    const tileCtx = tile.getContext('2d');
+    tileCtx.fillStyle = 'red';
+    tileCtx.fillRect(0, 0, 5, 5);
+
+    const render = (transform) => {
+      const target = new Canvas(60, 60);
+      const targetCtx = target.getContext('2d');
+      const square = targetCtx.createPattern(tile, 'no-repeat');
+      square.setTransform(transform);
+      targetCtx.fillStyle = square;
+      targetCtx.fillRect(0, 0, 60, 60);
+      return readPixels(target);
+    };
+
+    const red = [255, 0, 0, 255];
+    const transparent = [0, 0, 0, 0];
+
+    let pixel = render();
+    expect(pixel(2, 2)).toEqual(red);
+    expect(pixel(22, 22)).toEqual(transparent);
+
+    pixel = render({ e: 20, f: 20 });
+    expect(pixel(2, 2)).toEqual(transparent);
+    expect(pixel(22, 22)).toEqual(red);
+
+    pixel = render({ a: 4, d: 4 });
+    expect(pixel(10, 10)).toEqual(red);
+    expect(pixel(30, 30)).toEqual(transparent);
+  });
+
+  test('DOMMatrix', () => {
+    const identity = new DOMMatrix();
+    expect(identity.isIdentity).toBe(true);
+    expect(identity.is2D).toBe(true);
+    expect(identity.toString()).toBe('matrix(1, 0, 0, 1, 0, 0)');
+
+    const matrix = new DOMMatrix([2, 0, 0, 2, 10, 20]);
+    expect([matrix.a, matrix.d, matrix.e, matrix.f]).toEqual([2, 2, 10, 20]);
+    expect(matrix.m41).toBe(10);
+
+    const point = matrix.transformPoint({ x: 1, y: 1 });
+    expect(point).toBeInstanceOf(DOMPoint);
+    expect([point.x, point.y, point.z, point.w]).toEqual([12, 22, 0, 1]);
+
+    const inverse = matrix.inverse();
+    expect(inverse.toString()).toBe('matrix(0.5, 0, 0, 0.5, -5, -10)');
+    expect(matrix.multiply(inverse).isIdentity).toBe(true);
+
+    const rotated = new DOMMatrix().rotate(90);
+    expect(rotated.b).toBeCloseTo(1);
+    expect(rotated.c).toBeCloseTo(-1);
+
+    expect(new DOMMatrix().translate(5, 0, 1).is2D).toBe(false);
+    expect(new DOMMatrix().scale(2).a).toBe(2);
+    expect(new DOMMatrix().scale(2, 2, 1, 0, 0, 5).is2D).toBe(false);
+    expect(new DOMMatrix().scale(2, 2, 1, 10, 10, 0).is2D).toBe(true);
+    expect(new DOMMatrix().skewX(45).c).toBeCloseTo(1);
+    expect(new DOMMatrix([1, 1, 1, 1, 0, 0]).inverse().a).toBeNaN();
+
+    // Numbers are printed like JS prints them
+    for (const value of [1e-7, 0.000001, -0, 123.456, 1e21, 1.5e300, -2.5e-10]) {
+      expect(new DOMMatrix([1, 0, 0, 1, value, 0]).toString()).toBe(`matrix(1, 0, 0, 1, ${value}, 0)`);
+    }
+
+    const parsed = new DOMMatrix('translate(10px, 20px) scale(2)');
+    expect(parsed.toString()).toBe('matrix(2, 0, 0, 2, 10, 20)');
+    expect(new DOMMatrix('matrix(1, 2, 3, 4, 5, 6)').c).toBe(3);
+    expect(new DOMMatrix('rotateX(10deg)').is2D).toBe(false);
+    expect(() => new DOMMatrix('translate(10)')).toThrow(/SyntaxError/);
+    expect(() => new DOMMatrix([1, 2, 3])).toThrow(/TypeError/);
+
+    const self = new DOMMatrix();
+    expect(self.translateSelf(5, 5).scaleSelf(2)).toBe(self);
+    expect(self.toString()).toBe('matrix(2, 0, 0, 2, 5, 5)');
+    self.m13 = 1;
+    expect(self.is2D).toBe(false);
+    expect(self.toString()).toMatch(/^matrix3d\(/);
+    self.setMatrixValue('none');
+    expect(self.isIdentity).toBe(true);
+
+    const array = new DOMMatrix([1, 2, 3, 4, 5, 6]).toFloat64Array();
+    expect(Array.from(array)).toEqual([1, 2, 0, 0, 3, 4, 0, 0, 0, 0, 1, 0, 5, 6, 0, 1]);
+    expect(DOMMatrix.fromFloat32Array(new Float32Array([1, 0, 0, 1, 3, 4])).e).toBe(3);
+    expect(DOMMatrix.fromMatrix({ a: 2, m22: 3 }).d).toBe(3);
+    expect(() => DOMMatrix.fromMatrix({ a: 1, m11: 2 })).toThrow(/TypeError/);
+    expect(DOMMatrix.fromMatrix(matrix).multiply(new DOMMatrixReadOnly()).e).toBe(10);
+
+    const readOnly = new DOMMatrixReadOnly('scale(3)');
+    expect(readOnly.translate(1, 1)).toBeInstanceOf(DOMMatrix);
+    expect(readOnly.a).toBe(3);
+
+    const transformed = new DOMPoint(1, 2).matrixTransform(matrix);
+    expect([transformed.x, transformed.y]).toEqual([12, 24]);
+  });
+
+  test('add path with transform', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    const square = new Path2D();
+    square.rect(0, 0, 10, 10);
+
+    const path = new Path2D();
+    path.addPath(square, new DOMMatrix().translate(20, 20).scale(3));
+    path.addPath(square, { e: 70, f: 70 });
+    path.addPath(square, { a: NaN });
+    expect(() => path.addPath(square, { a: 1, m11: 2 })).toThrow(/TypeError/);
+
+    const buffer = canvas.toBuffer('image/png');
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('context transforms', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.getTransform().isIdentity).toBe(true);
+
+    ctx.translate(10, 20);
+    ctx.scale(2, 2);
+    expect(ctx.getTransform().toString()).toBe('matrix(2, 0, 0, 2, 10, 20)');
+
+    ctx.translate(NaN, 5);
+    ctx.scale(Infinity, 1);
+    ctx.transform(1, 0, 0, 1, NaN, 0);
+    expect(ctx.getTransform().toString()).toBe('matrix(2, 0, 0, 2, 10, 20)');
+
+    ctx.transform(1, 0, 0, 1, 5, 5);
+    expect(ctx.getTransform().e).toBe(20);
+
+    ctx.setTransform(1, 0, 0, 1, 3, 4);
+    expect(ctx.getTransform().toString()).toBe('matrix(1, 0, 0, 1, 3, 4)');
+
+    ctx.setTransform(new DOMMatrix().scale(3));
+    expect(ctx.getTransform().a).toBe(3);
+    ctx.setTransform({ a: NaN });
+    expect(ctx.getTransform().a).toBe(3);
+    expect(() => ctx.setTransform(1, 0)).toThrow(/TypeError/);
+
+    ctx.resetTransform();
+    ctx.rotate(Math.PI / 2);
+    expect(ctx.getTransform().b).toBeCloseTo(1);
+
+    // The returned matrix is a copy
+    const matrix = ctx.getTransform();
+    matrix.a = 5;
+    expect(ctx.getTransform().a).toBeCloseTo(0);
+
+    ctx.resetTransform();
+    expect(ctx.setTransform()).toBeUndefined();
+
+    // Path points are transformed when they are added
+    ctx.beginPath();
+    ctx.moveTo(0, 0);
+    ctx.translate(50, 50);
+    ctx.lineTo(0, 0);
+    ctx.resetTransform();
+    ctx.lineWidth = 4;
+    ctx.stroke();
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'transforms.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('save and restore the drawing state', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    const gradient = ctx.createLinearGradient(0, 0, 100, 0);
+    ctx.fillStyle = gradient;
+    ctx.strokeStyle = 'red';
+    ctx.lineWidth = 3;
+    ctx.imageSmoothingEnabled = false;
+    ctx.translate(10, 10);
+
+    ctx.save();
+    ctx.fillStyle = 'blue';
+    ctx.strokeStyle = 'green';
+    ctx.lineWidth = 7;
+    ctx.imageSmoothingEnabled = true;
+    ctx.scale(2, 2);
+
+    ctx.save();
+    ctx.resetTransform();
+    ctx.restore();
+    expect(ctx.getTransform().toString()).toBe('matrix(2, 0, 0, 2, 10, 10)');
+
+    ctx.restore();
+    expect(ctx.fillStyle).toBe(gradient);
+    expect(ctx.strokeStyle).toBe('#ff0000');
+    expect(ctx.lineWidth).toBe(3);
+    expect(ctx.imageSmoothingEnabled).toBe(false);
+    expect(ctx.getTransform().toString()).toBe('matrix(1, 0, 0, 1, 10, 10)');
+
+    // Restoring without a saved state does nothing
+    ctx.restore();
+    expect(ctx.lineWidth).toBe(3);
+    expect(ctx.getTransform().e).toBe(10);
+  });
+
+  test('clip', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    ctx.save();
+    ctx.beginPath();
+    ctx.rect(10, 10, 80, 80);
+    ctx.rect(30, 30, 40, 40);
+    ctx.clip('evenodd');
+
+    const circle = new Path2D();
+    circle.arc(50, 50, 45, 0, 2 * Math.PI);
+    ctx.clip(circle);
+    ctx.clip(circle, 'nonzero');
+
+    ctx.fillStyle = 'green';
+    ctx.fillRect(0, 0, 100, 100);
+    ctx.restore();
+
+    // The clip is gone after restore
+    ctx.fillStyle = 'blue';
+    ctx.fillRect(0, 0, 10, 10);
+
+    expect(() => ctx.clip('inside')).toThrow(/TypeError/);
+    expect(() => ctx.clip(circle, 'inside')).toThrow(/TypeError/);
+
+    fs.writeFileSync(path.join(outputDir, 'clip.png'), canvas.toBuffer('image/png'));
+
+    // The evenodd clip leaves a hole, and the circle cuts the corners
+    const pixel = readPixels(canvas);
+    expect(pixel(20, 50)).toEqual([0, 128, 0, 255]);
+    expect(pixel(50, 50)).toEqual([0, 0, 0, 0]);
+    expect(pixel(14, 14)).toEqual([0, 0, 0, 0]);
+    expect(pixel(5, 5)).toEqual([0, 0, 255, 255]);
+  });
+
+  test('fill rules tell apart self-intersecting paths', () => {
+    // A five-pointed star drawn in one stroke winds twice around its center
+    const star = new Path2D();
+    for (let i = 0; i < 5; i++) {
+      const angle = -Math.PI / 2 + (i * 4 * Math.PI) / 5;
+      const [x, y] = [50 + 40 * Math.cos(angle), 50 + 40 * Math.sin(angle)];
+      i === 0 ? star.moveTo(x, y) : star.lineTo(x, y);
+    }
+    star.closePath();
+
+    const green = [0, 128, 0, 255];
+    const transparent = [0, 0, 0, 0];
+
+    for (const [fillRule, center] of [['nonzero', green], ['evenodd', transparent]]) {
+      const filled = new Canvas(100, 100);
+      const fillCtx = filled.getContext('2d');
+      fillCtx.fillStyle = 'green';
+      fillCtx.fill(star, fillRule);
+
+      const clipped = new Canvas(100, 100);
+      const clipCtx = clipped.getContext('2d');
+      clipCtx.clip(star, fillRule);
+      clipCtx.fillStyle = 'green';
+      clipCtx.fillRect(0, 0, 100, 100);
+
+      for (const canvas of [filled, clipped]) {
+        const pixel = readPixels(canvas);
+        expect(pixel(50, 50)).toEqual(center);
+        // Inside the top point, which is covered once
+        expect(pixel(50, 20)).toEqual(green);
+        expect(pixel(5, 95)).toEqual(transparent);
+      }
+    }
+  });
+
+  test('fill and stroke Path2D with fill rules', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    const ring = new Path2D();
+    ring.arc(50, 50, 40, 0, 2 * Math.PI);
+    ring.arc(50, 50, 20, 0, 2 * Math.PI);
+
+    ctx.fillStyle = 'blue';
+    ctx.fill(ring, 'evenodd');
+    ctx.lineWidth = 2;
+    ctx.stroke(ring);
+
+    ctx.beginPath();
+    ctx.rect(110, 10, 80, 80);
+    ctx.rect(130, 30, 40, 40);
+    ctx.fill('evenodd');
+    ctx.fill();
+    ctx.stroke();
+
+    expect(() => ctx.fill('inside')).toThrow(/TypeError/);
+    expect(() => ctx.fill(ring, 'inside')).toThrow(/TypeError/);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'fill-rules.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('hit testing', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    ctx.beginPath();
+    ctx.rect(10, 10, 80, 80);
+    ctx.rect(30, 30, 40, 40);
+    expect(ctx.isPointInPath(50, 50)).toBe(true);
+    expect(ctx.isPointInPath(50, 50, 'evenodd')).toBe(false);
+    expect(ctx.isPointInPath(20, 20, 'evenodd')).toBe(true);
+    expect(ctx.isPointInPath(5, 5)).toBe(false);
+    expect(ctx.isPointInPath(NaN, 5)).toBe(false);
+
+    const square = new Path2D();
+    square.rect(0, 0, 10, 10);
+    expect(ctx.isPointInPath(square, 5, 5)).toBe(true);
+
+    // The path is transformed, the point is not
+    ctx.translate(50, 50);
+    expect(ctx.isPointInPath(square, 5, 5)).toBe(false);
+    expect(ctx.isPointInPath(square, 55, 55)).toBe(true);
+    ctx.resetTransform();
+
+    ctx.lineWidth = 10;
+    expect(ctx.isPointInStroke(square, 0, 5)).toBe(true);
+    expect(ctx.isPointInStroke(square, 4, 5)).toBe(true);
+    expect(ctx.isPointInStroke(square, 6, 5)).toBe(false);
+    expect(ctx.isPointInStroke(12, 50)).toBe(true);
+    expect(ctx.isPointInStroke(50, 50)).toBe(false);
+
+    ctx.scale(4, 4);
+    expect(ctx.isPointInStroke(square, 18, 20)).toBe(true);
+
+    expect(() => ctx.isPointInPath(square, 1)).toThrow(/TypeError/);
+    expect(() => ctx.isPointInPath(1, 1, 'inside')).toThrow(/TypeError/);
+  });
+
+  test('line caps, joins and miter limit', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.lineCap).toBe('butt');
+    expect(ctx.lineJoin).toBe('miter');
+    expect(ctx.miterLimit).toBe(10);
+
+    ctx.lineCap = 'round';
+    ctx.lineCap = 'Round';
+    ctx.lineJoin = 'bevel';
+    ctx.lineJoin = 'sharp';
+    ctx.miterLimit = 4;
+    ctx.miterLimit = 0;
+    ctx.miterLimit = -1;
+    ctx.miterLimit = Infinity;
+    expect(ctx.lineCap).toBe('round');
+    expect(ctx.lineJoin).toBe('bevel');
+    expect(ctx.miterLimit).toBe(4);
+
+    ctx.save();
+    ctx.lineCap = 'square';
+    ctx.restore();
+    expect(ctx.lineCap).toBe('round');
+
+    // A square cap extends the line past its end point
+    const line = new Path2D();
+    line.moveTo(20, 50);
+    line.lineTo(80, 50);
+    ctx.lineWidth = 10;
+    ctx.lineCap = 'butt';
+    expect(ctx.isPointInStroke(line, 83, 50)).toBe(false);
+    ctx.lineCap = 'square';
+    expect(ctx.isPointInStroke(line, 83, 50)).toBe(true);
+    ctx.stroke(line);
+
+    ctx.lineJoin = 'round';
+    ctx.strokeRect(110, 20, 60, 60);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'line-styles.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('line dashes', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.getLineDash()).toEqual([]);
+    expect(ctx.lineDashOffset).toBe(0);
+
+    ctx.setLineDash([5, 10, 15]);
+    expect(ctx.getLineDash()).toEqual([5, 10, 15, 5, 10, 15]);
+
+    ctx.setLineDash([1, -1]);
+    ctx.setLineDash([1, NaN]);
+    expect(ctx.getLineDash()).toEqual([5, 10, 15, 5, 10, 15]);
+
+    // getLineDash returns a copy
+    ctx.getLineDash().push(1);
+    expect(ctx.getLineDash()).toHaveLength(6);
+
+    ctx.lineDashOffset = 3;
+    ctx.lineDashOffset = Infinity;
+    expect(ctx.lineDashOffset).toBe(3);
+
+    ctx.save();
+    ctx.setLineDash([]);
+    ctx.restore();
+    expect(ctx.getLineDash()).toHaveLength(6);
+
+    // Points in the gaps are not in the stroke
+    const line = new Path2D();
+    line.moveTo(0, 50);
+    line.lineTo(200, 50);
+    ctx.lineWidth = 4;
+    ctx.lineDashOffset = 0;
+    ctx.setLineDash([10, 10]);
+    expect(ctx.isPointInStroke(line, 5, 50)).toBe(true);
+    expect(ctx.isPointInStroke(line, 15, 50)).toBe(false);
+    ctx.lineDashOffset = 10;
+    expect(ctx.isPointInStroke(line, 5, 50)).toBe(false);
+
+    ctx.stroke(line);
+    ctx.strokeRect(20, 10, 160, 80);
+    ctx.strokeText('Skinvas', 80, 30);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'line-dash.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('global alpha and composite operation', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.globalAlpha).toBe(1);
+    expect(ctx.globalCompositeOperation).toBe('source-over');
+
+    ctx.globalAlpha = 0.5;
+    ctx.globalAlpha = 2;
+    ctx.globalAlpha = -1;
+    ctx.globalAlpha = NaN;
+    expect(ctx.globalAlpha).toBe(0.5);
+
+    ctx.globalCompositeOperation = 'multiply';
+    ctx.globalCompositeOperation = 'not-an-operation';
+    expect(ctx.globalCompositeOperation).toBe('multiply');
+
+    for (const operation of ['color-dodge', 'destination-atop', 'lighter', 'luminosity']) {
+      ctx.globalCompositeOperation = operation;
+      expect(ctx.globalCompositeOperation).toBe(operation);
+    }
+
+    ctx.save();
+    ctx.globalAlpha = 0.25;
+    ctx.globalCompositeOperation = 'xor';
+    ctx.restore();
+    expect(ctx.globalAlpha).toBe(0.5);
+    expect(ctx.globalCompositeOperation).toBe('luminosity');
+
+    ctx.globalCompositeOperation = 'source-over';
+    ctx.fillStyle = 'red';
+    ctx.fillRect(10, 10, 80, 80);
+
+    ctx.globalCompositeOperation = 'multiply';
+    ctx.fillStyle = 'blue';
+    ctx.fillRect(50, 50, 80, 40);
+    ctx.strokeText('Skinvas', 110, 30);
+
+    const buffer = canvas.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'compositing.png'), buffer);
+
+    expect(buffer.length).toBeGreaterThan(0);
+  });
+
+  test('shadows', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.shadowBlur).toBe(0);
+    expect(ctx.shadowColor).toBe('rgba(0, 0, 0, 0)');
+    expect(ctx.shadowOffsetX).toBe(0);
+    expect(ctx.shadowOffsetY).toBe(0);
+
+    ctx.shadowBlur = 8;
+    ctx.shadowBlur = -1;
+    ctx.shadowBlur = Infinity;
+    expect(ctx.shadowBlur).toBe(8);
+
+    ctx.shadowColor = 'red';
+    ctx.shadowColor = 'not-a-color';
+    expect(ctx.shadowColor).toBe('#ff0000');
+
+    ctx.shadowOffsetX = 5;
+    ctx.shadowOffsetY = -5;
+    ctx.shadowOffsetX = NaN;
+    expect(ctx.shadowOffsetX).toBe(5);
+    expect(ctx.shadowOffsetY).toBe(-5);
+
+    ctx.save();
+    ctx.shadowColor = 'blue';
+    ctx.shadowBlur = 0;
+    ctx.restore();
+    expect(ctx.shadowColor).toBe('#ff0000');
+    expect(ctx.shadowBlur).toBe(8);
+
+    ctx.fillStyle = 'green';
+    ctx.fillRect(10, 20, 50, 50);
+
+    // The offset is not affected by the transform
+    ctx.save();
+    ctx.translate(80, 20);
+    ctx.scale(2, 2);
+    ctx.strokeRect(0, 0, 20, 20);
+    ctx.restore();
+
+    ctx.fillText('Skinvas', 140, 50);
+
+    fs.writeFileSync(path.join(outputDir, 'shadows.png'), canvas.toBuffer('image/png'));
+
+    // The shape covers its own shadow, which shows past its right edge
+    const pixel = readPixels(canvas);
+    expect(pixel(35, 45)).toEqual([0, 128, 0, 255]);
+    expect(pixel(62, 40)[0]).toBe(255);
+    expect(pixel(62, 40)[3]).toBeGreaterThan(0);
+  });
+
+  test('shadow offset and blur are in canvas pixels', () => {
+    const canvas = new Canvas(120, 60);
+    const ctx = canvas.getContext('2d');
+
+    // Under scale(2) the square covers 10..30, and the unscaled offset puts
+    // its shadow at 30..50 across and 20..40 down
+    ctx.shadowColor = 'red';
+    ctx.shadowOffsetX = 20;
+    ctx.shadowOffsetY = 10;
+    ctx.fillStyle = 'green';
+    ctx.scale(2, 2);
+    ctx.fillRect(5, 5, 10, 10);
+
+    let pixel = readPixels(canvas);
+    expect(pixel(20, 20)).toEqual([0, 128, 0, 255]);
+    expect(pixel(40, 35)).toEqual([255, 0, 0, 255]);
+    expect(pixel(25, 35)).toEqual([0, 0, 0, 0]);
+    expect(pixel(60, 45)).toEqual([0, 0, 0, 0]);
+
+    // A blurred shadow is solid in the middle and fades across its edge
+    ctx.resetTransform();
+    ctx.clearRect(0, 0, 120, 60);
+    ctx.shadowOffsetX = 50;
+    ctx.shadowOffsetY = 0;
+    ctx.shadowBlur = 8;
+    ctx.fillRect(10, 10, 40, 40);
+
+    pixel = readPixels(canvas);
+    expect(pixel(30, 30)).toEqual([0, 128, 0, 255]);
+    expectPixelClose(pixel(80, 30), [255, 0, 0, 255]);
+    expect(pixel(60, 30)[3]).toBeGreaterThan(64);
+    expect(pixel(60, 30)[3]).toBeLessThan(192);
+    expect(pixel(56, 30)[3]).toBeGreaterThan(0);
+    expect(pixel(56, 30)[3]).toBeLessThan(100);
+  });
+
+  test('clear only the given rectangle', () => {
+    const canvas = new Canvas(200, 100);
+    const ctx = canvas.getContext2D();
+
+    ctx.fillStyle = 'blue';
+    ctx.fillRect(0, 0, 200, 100);
+
+    ctx.shadowColor = 'red';
+    ctx.shadowBlur = 4;
+    ctx.globalAlpha = 0.5;
+    ctx.clearRect(10, 10, 30, 30);
+    ctx.clearRect(NaN, 0, 10, 10);
+
+    // The rectangle is transformed and clipped
+    ctx.save();
+    ctx.beginPath();
+    ctx.rect(100, 0, 100, 50);
+    ctx.clip();
+    ctx.translate(120, 20);
+    ctx.rotate(Math.PI / 4);
+    ctx.clearRect(-20, -20, 40, 40);
+    ctx.restore();
+
+    fs.writeFileSync(path.join(outputDir, 'clear-rect.png'), canvas.toBuffer('image/png'));
+
+    const blue = [0, 0, 255, 255];
+    const pixel = readPixels(canvas);
+
+    // Only the rectangle is cleared, without shadow or alpha
+    expect(pixel(25, 25)).toEqual([0, 0, 0, 0]);
+    expect(pixel(5, 5)).toEqual(blue);
+    expect(pixel(45, 25)).toEqual(blue);
+    expect(pixel(25, 45)).toEqual(blue);
+
+    // The rotated square is cleared inside the clip only
+    expect(pixel(120, 20)).toEqual([0, 0, 0, 0]);
+    expect(pixel(120, 45)).toEqual([0, 0, 0, 0]);
+    expect(pixel(96, 20)).toEqual(blue);
+    expect(pixel(138, 38)).toEqual(blue);
+  });
+
+  test('context attributes', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext2D();
+
+    expect(ctx.getContextAttributes()).toEqual({
+      alpha: true,
+      colorSpace: 'srgb',
+      desynchronized: false,
+      willReadFrequently: false,
+      pixelFormat: 'rgba8',
+    });
+
+    // Only the first call chooses the attributes
+    expect(canvas.getContext2D({ alpha: false }).getContextAttributes().alpha).toBe(true);
+
+    const opaque = new Canvas(100, 100);
+    const opaqueCtx = opaque.getContext2D({
+      alpha: false,
+      colorSpace: 'display-p3',
+      willReadFrequently: true,
+      pixelFormat: 'rgba16f',
+    });
+
+    expect(opaqueCtx.getContextAttributes()).toEqual({
+      alpha: false,
+      colorSpace: 'display-p3',
+      desynchronized: false,
+      willReadFrequently: true,
+      pixelFormat: 'rgba16f',
+    });
+
+    // Resizing keeps the attributes
+    opaque.resize(50, 50);
+    expect(opaqueCtx.getContextAttributes().pixelFormat).toBe('rgba16f');
+
+    opaqueCtx.fillStyle = 'color(display-p3 1 0 0)';
+    opaqueCtx.fillRect(10, 10, 30, 30);
+
+    const buffer = opaque.toBuffer('image/png');
+    fs.writeFileSync(path.join(outputDir, 'context-attributes.png'), buffer);
+    expect(buffer.length).toBeGreaterThan(0);
+
+    expect(() => new Canvas(10, 10).getContext2D({ colorSpace: 'rec2020' })).toThrow(/TypeError/);
+    expect(() => new Canvas(10, 10).getContext2D({ pixelFormat: 'rgb565' })).toThrow(/TypeError/);
+  });
+
+  test('getContext returns a persistent context', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext('2d');
+
+    expect(ctx).toBeInstanceOf(CanvasRenderingContext2D);
+    expect(canvas.getContext('2d')).toBe(ctx);
+    expect(canvas.getContext2D()).toBe(ctx);
+    expect(ctx.canvas).toBe(canvas);
+
+    // State set through one handle is seen through another
+    ctx.fillStyle = 'red';
+    ctx.translate(10, 20);
+    const again = canvas.getContext('2d');
+    expect(again.fillStyle).toBe('#ff0000');
+    expect(again.getTransform().e).toBe(10);
+
+    expect(canvas.getContext('webgl')).toBeNull();
+    expect(canvas.getContext('bitmaprenderer')).toBeNull();
+
+    // Resizing keeps the context
+    canvas.width = 50;
+    expect(canvas.getContext('2d')).toBe(ctx);
+  });
+
+  test('context draws to the resized surface', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext('2d');
+
+    ctx.fillStyle = 'red';
+    ctx.fillRect(0, 0, 100, 100);
+
+    canvas.resize(300, 150);
+    ctx.fillStyle = 'blue';
+    ctx.fillRect(200, 100, 50, 50);
+
+    const image = new Image();
+    image.src = canvas.toBuffer('image/png');
+    expect(image.width).toBe(300);
+    expect(image.height).toBe(150);
+
+    // Patterns can be created from the context's own canvas
+    ctx.fillStyle = ctx.createPattern(canvas, 'repeat');
+    ctx.fillRect(0, 0, 50, 50);
+
+    expect(canvas.toBuffer('image/png').length).toBeGreaterThan(0);
+  });
+
+  test('ctx.canvas outlives the canvas variable', () => {
+    v8.setFlagsFromString('--expose-gc');
+    const gc = vm.runInNewContext('gc');
+
+    // Only the context is kept, the canvas is unreachable from here
+    const ctx = (() => new Canvas(30, 20).getContext('2d'))();
+    gc();
+
+    expect(ctx.canvas).toBeInstanceOf(Canvas);
+    expect(ctx.canvas.width).toBe(30);
+    expect(ctx.canvas.getContext('2d')).toBe(ctx);
+  });
+
+  test('resizing resets the context state', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext('2d');
+
+    ctx.save();
+    ctx.fillStyle = 'red';
+    ctx.translate(10, 20);
+    ctx.rect(0, 0, 50, 50);
+    ctx.save();
+
+    canvas.width = 50;
+
+    expect(ctx.fillStyle).toBe('#000000');
+    expect(ctx.getTransform().isIdentity).toBe(true);
+    expect(ctx.isPointInPath(20, 30)).toBe(false);
+
+    // The saved states are gone, so restore() has nothing to pop
+    ctx.fillStyle = 'blue';
+    ctx.restore();
+    expect(ctx.fillStyle).toBe('#0000ff');
+
+    ctx.clip();
+    ctx.restore();
+    ctx.fillRect(0, 0, 50, 50);
+    fs.writeFileSync(path.join(outputDir, 'resize-reset.png'), canvas.toBuffer('image/png'));
+  });
+
+  test('the current path stays in place across transform changes', () => {
+    const canvas = new Canvas(100, 100);
+    const ctx = canvas.getContext('2d');
+
+    // A singular transform in between doesn't lose the first point
+    ctx.moveTo(10, 10);
+    ctx.save();
+    ctx.scale(0, 0);
+    ctx.restore();
+    ctx.lineTo(20, 20);
+    ctx.stroke();
+    expect(ctx.isPointInStroke(15, 15)).toBe(true);
+
+    // Points are placed with the transform at the time they are added
+    ctx.beginPath();
+    ctx.translate(50, 0);
+    ctx.rect(0, 0, 10, 10);
+    ctx.resetTransform();
+    expect(ctx.isPointInPath(55, 5)).toBe(true);
+    expect(ctx.isPointInPath(5, 5)).toBe(false);
+
+    // Nothing is drawn while the transform is singular
+    ctx.scale(0, 1);
+    ctx.fill();
+    ctx.stroke();
+    expect(ctx.isPointInStroke(50, 5)).toBe(false);
+  });
+
+  test('full canvas composite operations clear outside the shape', () => {
+    // What each operation leaves of a blue canvas after filling a red square
+    const expected = {
+      'source-in': ['red'],
+      'source-out': [],
+      'destination-in': ['blue'],
+      'destination-atop': ['blue'],
+      'copy': ['red'],
+    };
+
+    for (const [operation, fills] of Object.entries(expected)) {
+      const canvas = new Canvas(60, 60);
+      const ctx = canvas.getContext('2d');
+      ctx.fillStyle = 'blue';
+      ctx.fillRect(0, 0, 60, 60);
+      ctx.globalCompositeOperation = operation;
+      ctx.fillStyle = 'red';
+      ctx.fillRect(20, 20, 20, 20);
+
+      const reference = new Canvas(60, 60);
+      const referenceCtx = reference.getContext('2d');
+      for (const fill of fills) {
+        referenceCtx.fillStyle = fill;
+        referenceCtx.fillRect(20, 20, 20, 20);
+      }
+
+      expect(canvas.toBuffer('image/png').equals(reference.toBuffer('image/png'))).toBe(true);
+    }
+  });
+
+  test('wide gamut colors are kept on display-p3 canvases', () => {
+    const draw = (color) => {
+      const canvas = new Canvas(20, 20);
+      const ctx = canvas.getContext('2d', { colorSpace: 'display-p3' });
+      ctx.fillStyle = color;
+      ctx.fillRect(0, 0, 20, 20);
+      return canvas.toBuffer('image/png');
+    };
+
+    // Display P3 red is outside sRGB, so it isn't drawn as sRGB red
+    expect(draw('color(display-p3 1 0 0)').equals(draw('red'))).toBe(false);
+
+    // Getters still serialize to the sRGB gamut
+    const ctx = new Canvas(20, 20).getContext('2d');
+    ctx.fillStyle = 'color(display-p3 1 0 0)';
+    expect(ctx.fillStyle).toBe('#ff0000');
+  });
+
+  test('canvases are accepted as image sources', () => {
+    const source = new Canvas(30, 20);
+    source.getContext('2d').fillRect(0, 0, 30, 20);
+
+    const ctx = new Canvas(100, 100).getContext('2d');
+    expect(ctx.createPattern(source, 'repeat')).toBeInstanceOf(CanvasPattern);
+
+    const bitmap = createImageBitmap(source);
+    expect(bitmap.width).toBe(30);
+    expect(bitmap.height).toBe(20);
+  });
+
+  test('gradient interpolation survives a round trip through its getter', () => {
+    const render = (interpolation) => {
+      const canvas = new Canvas(100, 10);
+      const ctx = canvas.getContext('2d');
+      const gradient = ctx.createLinearGradient(0, 0, 100, 0);
+      gradient.addColorStop(0, 'rgba(255, 0, 0, 0.2)');
+      gradient.addColorStop(1, 'blue');
+      if (interpolation !== undefined) {
+        gradient.interpolation = interpolation;
+      }
+      gradient.interpolation = gradient.interpolation;
+      ctx.fillStyle = gradient;
+      ctx.fillRect(0, 0, 100, 10);
+      return readPixels(canvas)(50, 5);
+    };
+
+    // The default blends unpremultiplied, unlike an explicit 'srgb'
+    const untouched = render();
+    expect(render(null)).toEqual(untouched);
+    expect(render('srgb')).not.toEqual(untouched);
+    expect(render('srgb')).toEqual(render('srgb'));
+  });
+
+  test('hsl saturation and lightness are clamped', () => {
+    // Colors are kept as extended sRGB on display-p3 canvases, so anything
+    // left unclamped would draw differently from the in-range color
+    const draw = (color) => {
+      const canvas = new Canvas(10, 10);
+      const ctx = canvas.getContext('2d', { colorSpace: 'display-p3' });
+      ctx.fillStyle = color;
+      ctx.fillRect(0, 0, 10, 10);
+      return canvas.toBuffer('image/png');
+    };
+
+    expect(draw('hsl(0 150% 50%)').equals(draw('hsl(0 100% 50%)'))).toBe(true);
+    expect(draw('hsl(0 100% 120%)').equals(draw('white'))).toBe(true);
+    expect(draw('hsl(0 150% 50%)').equals(draw('hsl(0 90% 50%)'))).toBe(false);
+  });
 });